@@ -0,0 +1,184 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::{record_save, validate_memo_path, write_memo, CommandError};
+use crate::crypto::CryptoState;
+use crate::git::GitState;
+use crate::settings::SettingsState;
+use crate::vault::VaultState;
+
+/// A memo that keeps being edited is still saved at least this often
+const MAX_PENDING_AGE: Duration = Duration::from_secs(10);
+const WORKER_INTERVAL: Duration = Duration::from_millis(100);
+/// Emitted with the memo path when a queued save starts being written
+pub const SAVE_STARTED_EVENT: &str = "save-started";
+/// Emitted with the saved memo's `MemoMeta`
+pub const SAVE_FINISHED_EVENT: &str = "save-finished";
+/// Emitted with a `SaveFailed` when a queued save can't be written
+pub const SAVE_FAILED_EVENT: &str = "save-failed";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SaveFailed {
+    pub path: String,
+    pub error: String,
+}
+
+struct PendingSave {
+    /// Working folder the memo was queued in; a vault switch doesn't redirect the write
+    folder: PathBuf,
+    file_path: String,
+    content: String,
+    delay: Duration,
+    first_queued: Instant,
+    last_queued: Instant,
+}
+
+impl PendingSave {
+    fn is_memo(&self, folder: &Path, file_path: &str) -> bool {
+        self.folder == folder && self.file_path == file_path
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        now - self.last_queued >= self.delay || now - self.first_queued >= MAX_PENDING_AGE
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    pending: Mutex<HashMap<PathBuf, PendingSave>>,
+    /// Held while writing, so the worker and a flush never write the same memo out of order
+    writing: Mutex<()>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Managed state holding the memo contents waiting to be written by autosave
+pub struct AutosaveState {
+    queue: Arc<Queue>,
+    app: AppHandle,
+}
+
+impl AutosaveState {
+    pub fn new(app: AppHandle) -> Self {
+        AutosaveState {
+            queue: Arc::default(),
+            app,
+        }
+    }
+
+    /// Drops the queued save of a memo, e.g. because it was just saved explicitly or deleted
+    pub fn discard(&self, folder: &Path, file_path: &str) {
+        let _writing = lock(&self.queue.writing);
+        lock(&self.queue.pending).retain(|_, save| !save.is_memo(folder, file_path));
+    }
+
+    /// Writes the queued save of a memo now, before a command renames or edits the file
+    pub fn flush_memo(&self, folder: &Path, file_path: &str) {
+        write_pending(&self.app, &self.queue, |save| save.is_memo(folder, file_path));
+    }
+
+    /// Writes every queued save now, whether due or not
+    pub fn flush(&self) {
+        write_pending(&self.app, &self.queue, |_| true);
+    }
+
+    /// Starts the background thread writing the saves that are due.
+    /// Called once from `run()` at startup.
+    pub fn start_worker(&self) {
+        let (app, queue) = (self.app.clone(), Arc::clone(&self.queue));
+        thread::spawn(move || loop {
+            thread::sleep(WORKER_INTERVAL);
+            let now = Instant::now();
+            write_pending(&app, &queue, |save| save.is_due(now));
+        });
+    }
+}
+
+/// Writes the queued saves selected by `filter`, reporting each through events
+fn write_pending(app: &AppHandle, queue: &Queue, filter: impl Fn(&PendingSave) -> bool) {
+    let _writing = lock(&queue.writing);
+    let due: Vec<PendingSave> = {
+        let mut pending = lock(&queue.pending);
+        let keys: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, save)| filter(save))
+            .map(|(path, _)| path.clone())
+            .collect();
+        keys.iter().filter_map(|path| pending.remove(path)).collect()
+    };
+    if due.is_empty() {
+        return;
+    }
+
+    let vaults = app.state::<VaultState>();
+    let crypto = app.state::<CryptoState>();
+    let git = app.state::<GitState>();
+    for save in due {
+        let _ = app.emit(SAVE_STARTED_EVENT, &save.file_path);
+        match write_memo(&save.folder, &crypto, &save.file_path, &save.content, None, None) {
+            Ok(mut meta) => {
+                let registry = vaults.lock();
+                // IDs, pins and auto-commit belong to the active vault; skip them after a switch
+                if registry.active_folder().is_ok_and(|folder| folder == save.folder) {
+                    record_save(&registry, &git, &mut meta);
+                }
+                drop(registry);
+                let _ = app.emit(SAVE_FINISHED_EVENT, &meta);
+            }
+            Err(error) => {
+                let _ = app.emit(
+                    SAVE_FAILED_EVENT,
+                    SaveFailed {
+                        path: save.file_path,
+                        error: error.to_string(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Queues a memo's content to be written once it hasn't changed for the autosave delay.
+/// Repeated calls for the same memo replace the queued content and restart the delay.
+#[tauri::command]
+pub fn queue_save(
+    state: State<'_, VaultState>,
+    settings: State<'_, SettingsState>,
+    autosave: State<'_, AutosaveState>,
+    file_path: String,
+    content: String,
+) -> Result<(), CommandError> {
+    let folder = state.lock().active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, _) = validate_memo_path(&folder, &file_path)?;
+    let delay = Duration::from_millis(settings.get().autosave.delay_ms);
+
+    let now = Instant::now();
+    let mut pending = lock(&autosave.queue.pending);
+    let first_queued = pending.get(&path).map_or(now, |save| save.first_queued);
+    pending.insert(
+        path,
+        PendingSave {
+            folder,
+            file_path,
+            content,
+            delay,
+            first_queued,
+            last_queued: now,
+        },
+    );
+    Ok(())
+}
+
+/// Writes all queued saves immediately, e.g. before switching memos
+#[tauri::command]
+pub fn flush_saves(autosave: State<'_, AutosaveState>) {
+    autosave.flush();
+}
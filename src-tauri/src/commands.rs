@@ -1,8 +1,25 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
-use tauri::Manager;
+use tauri::State;
+use crate::autosave::AutosaveState;
+use crate::crypto::{self, CryptoState};
+use crate::encoding::{self, LineEnding};
+use crate::folder_config;
+use crate::fonts::{self, FontState};
+use crate::git::GitState;
+use crate::ids;
+use crate::index::{self, IndexState};
+use crate::listing::{self, ListMemosOptions, MemoPage};
+use crate::pins;
+use crate::session;
+use crate::stats::MemoStats;
+use crate::storage::now_millis;
+use crate::vault::{self, Vault, VaultRegistry, VaultState};
+use globset::GlobSet;
+use ignore::WalkBuilder;
 use tauri_plugin_dialog::DialogExt;
 use thiserror::Error;
 use tokio::sync::oneshot;
@@ -21,8 +38,11 @@ pub enum CommandError {
     #[error("Invalid file name: {0}")]
     InvalidFileName(String),
 
-    #[error("Only .md files are allowed")]
-    NotMarkdownFile,
+    #[error("Only memo files (.md, .markdown, .mdx, .txt) are allowed")]
+    NotMemoFile,
+
+    #[error("Unsupported memo extension: {0}")]
+    UnsupportedExtension(String),
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -38,6 +58,63 @@ pub enum CommandError {
 
     #[error("Path error: {0}")]
     PathError(String),
+
+    #[error("Unsupported encoding: {0}")]
+    UnsupportedEncoding(String),
+
+    #[error("File is not valid {0} text")]
+    InvalidEncoding(String),
+
+    #[error("Content contains characters that cannot be saved as {0}")]
+    UnencodableContent(String),
+
+    #[error("Memo too large to open at once: max {0} bytes, got {1} bytes")]
+    MemoTooLarge(u64, u64),
+
+    #[error("Vault not found: {0}")]
+    VaultNotFound(String),
+
+    #[error("No working folder is open")]
+    NoActiveFolder,
+
+    #[error("Invalid data file: {0}")]
+    InvalidData(String),
+
+    #[error("Line {0} is not a task")]
+    NotATask(usize),
+
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
+
+    #[error("Git error: {0}")]
+    GitError(String),
+
+    #[error("The working folder is not inside a git repository")]
+    NotARepository,
+
+    #[error("Sync failed: {0}")]
+    SyncError(String),
+
+    #[error("Encrypted memos are locked")]
+    EncryptionLocked,
+
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
+    #[error("Memo is encrypted")]
+    MemoEncrypted,
+
+    #[error("Memo is not encrypted")]
+    NotEncrypted,
+
+    #[error("Could not list system fonts: {0}")]
+    SystemFontError(String),
+
+    #[error("Invalid settings: {0}")]
+    InvalidSettings(String),
+
+    #[error("Invalid folder configuration: {0}")]
+    InvalidFolderConfig(String),
 }
 
 impl Serialize for CommandError {
@@ -60,25 +137,103 @@ impl CommandError {
             CommandError::InvalidFolder(_) => "invalid_folder",
             CommandError::AccessDenied(_) => "access_denied",
             CommandError::InvalidFileName(_) => "invalid_file_name",
-            CommandError::NotMarkdownFile => "not_markdown_file",
+            CommandError::NotMemoFile => "not_memo_file",
+            CommandError::UnsupportedExtension(_) => "unsupported_extension",
             CommandError::IoError(_) => "io_error",
             CommandError::DialogCancelled => "dialog_cancelled",
             CommandError::UnsupportedFontFormat(_) => "unsupported_font_format",
             CommandError::FileTooLarge(_, _) => "file_too_large",
             CommandError::PathError(_) => "path_error",
+            CommandError::UnsupportedEncoding(_) => "unsupported_encoding",
+            CommandError::InvalidEncoding(_) => "invalid_encoding",
+            CommandError::UnencodableContent(_) => "unencodable_content",
+            CommandError::MemoTooLarge(_, _) => "memo_too_large",
+            CommandError::VaultNotFound(_) => "vault_not_found",
+            CommandError::NoActiveFolder => "no_active_folder",
+            CommandError::InvalidData(_) => "invalid_data",
+            CommandError::NotATask(_) => "not_a_task",
+            CommandError::InvalidDate(_) => "invalid_date",
+            CommandError::GitError(_) => "git_error",
+            CommandError::NotARepository => "not_a_repository",
+            CommandError::SyncError(_) => "sync_error",
+            CommandError::EncryptionLocked => "encryption_locked",
+            CommandError::EncryptionError(_) => "encryption_error",
+            CommandError::MemoEncrypted => "memo_encrypted",
+            CommandError::NotEncrypted => "not_encrypted",
+            CommandError::SystemFontError(_) => "system_font_error",
+            CommandError::InvalidSettings(_) => "invalid_settings",
+            CommandError::InvalidFolderConfig(_) => "invalid_folder_config",
         }
     }
 }
 
-/// Validates that the file is a .md file
-fn is_markdown_file(path: &Path) -> bool {
+/// Extensions that may be listed, opened and edited as memos
+pub const ALLOWED_MEMO_EXTENSIONS: [&str; 4] = ["md", "markdown", "mdx", "txt"];
+pub const DEFAULT_MEMO_EXTENSION: &str = "md";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoKind {
+    Markdown,
+    Mdx,
+    Text,
+}
+
+impl MemoKind {
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "md" | "markdown" => Some(MemoKind::Markdown),
+            "mdx" => Some(MemoKind::Mdx),
+            "txt" => Some(MemoKind::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the lowercased extension of a path, if it has one
+fn lowercase_extension(path: &Path) -> Option<String> {
     path.extension()
-        .map(|ext| ext.eq_ignore_ascii_case("md"))
-        .unwrap_or(false)
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+/// Normalizes a user supplied extension (".MD" -> "md") and checks it is allowed
+pub(crate) fn normalize_extension(ext: &str) -> Result<String, CommandError> {
+    let normalized = ext.trim().trim_start_matches('.').to_ascii_lowercase();
+    if ALLOWED_MEMO_EXTENSIONS.contains(&normalized.as_str()) {
+        Ok(normalized)
+    } else {
+        Err(CommandError::UnsupportedExtension(ext.to_string()))
+    }
+}
+
+/// Resolves the set of extensions to list, defaulting to the vault's configured set
+fn resolve_extensions(extensions: Option<Vec<String>>, defaults: Vec<String>) -> Result<Vec<String>, CommandError> {
+    match extensions {
+        Some(list) if !list.is_empty() => list.iter().map(|ext| normalize_extension(ext)).collect(),
+        _ => Ok(defaults),
+    }
+}
+
+/// Returns the memo kind if the path has one of the given extensions (case-insensitive).
+/// Encrypted memos count by the extension in front of `.age`.
+pub(crate) fn memo_kind_in(path: &Path, extensions: &[String]) -> Option<MemoKind> {
+    let ext = lowercase_extension(&crypto::plaintext_path(path))?;
+    if !extensions.contains(&ext) {
+        return None;
+    }
+    MemoKind::from_extension(&ext)
+}
+
+/// Validates that the file has one of the allowed memo extensions
+pub(crate) fn memo_kind(path: &Path) -> Result<MemoKind, CommandError> {
+    lowercase_extension(&crypto::plaintext_path(path))
+        .and_then(|ext| MemoKind::from_extension(&ext))
+        .ok_or(CommandError::NotMemoFile)
 }
 
 /// Validates that the file_name doesn't contain path traversal characters
-fn is_safe_filename(name: &str) -> bool {
+pub(crate) fn is_safe_filename(name: &str) -> bool {
     let name = name.trim();
     if name.is_empty() {
         return true; // Empty is handled separately
@@ -98,23 +253,76 @@ fn is_within_folder(working_folder: &Path, target: &Path) -> Result<bool, Comman
     Ok(canonical_target.starts_with(&canonical_folder))
 }
 
+/// Validates a memo path supplied by the webview: it must have a memo extension,
+/// exist, and resolve to a file inside the active working folder
+pub(crate) fn validate_memo_path(working_folder: &Path, file_path: &str) -> Result<(PathBuf, MemoKind), CommandError> {
+    let path = PathBuf::from(file_path);
+    let kind = memo_kind(&path)?;
+
+    if !path.is_file() {
+        return Err(CommandError::FileNotFound(file_path.to_string()));
+    }
+
+    if !is_within_folder(working_folder, &path)? {
+        return Err(CommandError::AccessDenied(
+            "file is outside working folder".to_string(),
+        ));
+    }
+    Ok((path, kind))
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MemoMeta {
+    /// Stable ID that survives renames (see `ids.rs`)
+    pub id: String,
     pub path: String,
     pub name: String,
+    pub kind: MemoKind,
+    pub size: u64,
     pub modified_at: u64,
     pub created_at: u64,
+    pub pinned: bool,
+    /// Stored as `<name>.<ext>.age`; needs `unlock_encryption` before it can be read
+    pub encrypted: bool,
+    /// Plain-text excerpt of the body, only filled in when requested from `list_memos`
+    pub preview: Option<String>,
+    /// Only filled in when requested from `list_memos`
+    pub stats: Option<MemoStats>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MemoFile {
+    pub id: String,
     pub path: String,
     pub name: String,
+    pub kind: MemoKind,
+    pub encrypted: bool,
     pub content: String,
+    pub encoding: String,
+    pub has_bom: bool,
+    pub line_ending: LineEnding,
+    pub size: u64,
     pub modified_at: u64,
     pub created_at: u64,
 }
 
+/// A slice of a memo returned by `read_memo_range`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemoChunk {
+    pub path: String,
+    pub offset: u64,
+    pub next_offset: u64,
+    pub size: u64,
+    pub content: String,
+    pub encoding: String,
+    pub eof: bool,
+}
+
+/// Memos larger than this must be read in chunks unless the caller raises the limit
+pub(crate) const DEFAULT_MAX_MEMO_SIZE: u64 = 10 * 1024 * 1024;
+/// Upper bound for a single `read_memo_range` request
+const MAX_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
 fn get_file_times(path: &PathBuf) -> (u64, u64) {
     let metadata = fs::metadata(path).ok();
     let modified_at = metadata
@@ -132,138 +340,307 @@ fn get_file_times(path: &PathBuf) -> (u64, u64) {
     (modified_at, created_at)
 }
 
-#[tauri::command]
-pub async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, CommandError> {
-    let (tx, rx) = oneshot::channel();
-    app.dialog().file().pick_folder(move |folder_path| {
-        let _ = tx.send(folder_path);
-    });
+fn get_file_size(path: &PathBuf) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
 
-    match rx.await {
-        Ok(Some(path)) => Ok(Some(path.to_string())),
-        Ok(None) => Ok(None),
-        Err(_) => Err(CommandError::DialogCancelled),
-    }
+fn memo_name(path: &Path) -> String {
+    crypto::plaintext_path(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Untitled")
+        .to_string()
 }
 
-#[tauri::command]
-pub fn list_memos(folder_path: String) -> Result<Vec<MemoMeta>, CommandError> {
-    let path = PathBuf::from(&folder_path);
-    if !path.exists() || !path.is_dir() {
-        return Err(CommandError::InvalidFolder(folder_path));
+pub(crate) fn build_memo_meta(path: &PathBuf, kind: MemoKind) -> MemoMeta {
+    let (modified_at, created_at) = get_file_times(path);
+    MemoMeta {
+        id: String::new(),
+        path: path.to_string_lossy().to_string(),
+        name: memo_name(path),
+        kind,
+        size: get_file_size(path),
+        modified_at,
+        created_at,
+        pinned: false,
+        encrypted: crypto::is_encrypted(path),
+        preview: None,
+        stats: None,
     }
+}
 
+/// Collects the memos with one of the given extensions in a working folder (unsorted).
+/// Files excluded by `.gitignore` (including those of enclosing folders), `.memoignore` or the
+/// `ignore` globs of `.memo/config.toml` are left out. Listing, quick open, the index, tasks
+/// and reminders all go through here.
+/// IDs are left empty; callers fill them in with `ids::sync_ids`.
+pub(crate) fn scan_memos(folder: &Path, extensions: &[String]) -> Result<Vec<MemoMeta>, CommandError> {
+    // Validated by `load`, so building the set can't fail here
+    let ignored = folder_config::load_or_default(folder).ignore_set().unwrap_or_else(|_| GlobSet::empty());
     let mut memos = Vec::new();
-    let entries = fs::read_dir(&path)?;
+    let entries = WalkBuilder::new(folder)
+        .max_depth(Some(1))
+        // Dotfiles were always listed; only ignore files decide what is skipped
+        .hidden(false)
+        // Honor .gitignore even when the folder isn't (yet) a git repository
+        .require_git(false)
+        .add_custom_ignore_filename(folder_config::IGNORE_FILE)
+        .build();
 
     for entry in entries.flatten() {
         let file_path = entry.path();
-        if file_path.is_file() {
-            if let Some(ext) = file_path.extension() {
-                if ext == "md" {
-                    let name = file_path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("Untitled")
-                        .to_string();
-                    let (modified_at, created_at) = get_file_times(&file_path);
-                    memos.push(MemoMeta {
-                        path: file_path.to_string_lossy().to_string(),
-                        name,
-                        modified_at,
-                        created_at,
-                    });
-                }
+        let relative = file_path.strip_prefix(folder).unwrap_or(file_path);
+        if file_path.is_file() && !ignored.is_match(relative) {
+            if let Some(kind) = memo_kind_in(file_path, extensions) {
+                memos.push(build_memo_meta(&file_path.to_path_buf(), kind));
             }
         }
     }
-
-    memos.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
     Ok(memos)
 }
 
+/// Picks a folder with the native dialog and makes it the active working folder.
+/// The folder is registered as a vault, so the webview never supplies a raw path.
 #[tauri::command]
-pub fn read_memo(file_path: String, working_folder: String) -> Result<MemoFile, CommandError> {
-    let path = PathBuf::from(&file_path);
-    let folder = PathBuf::from(&working_folder);
+pub async fn select_folder(app: tauri::AppHandle, state: State<'_, VaultState>) -> Result<Option<Vault>, CommandError> {
+    let Some(folder) = vault::pick_folder(&app).await? else {
+        return Ok(None);
+    };
 
-    // Security: Validate .md extension
-    if !is_markdown_file(&path) {
-        return Err(CommandError::NotMarkdownFile);
-    }
+    let mut registry = state.lock();
+    let vault = registry.register(&folder, None)?;
+    registry.activate(&vault.id)?;
+    Ok(Some(vault))
+}
 
-    if !path.exists() || !path.is_file() {
-        return Err(CommandError::FileNotFound(file_path.clone()));
+/// Lists the memos of the working folder, sorted and optionally paged. Without options the
+/// whole folder is returned, newest first.
+#[tauri::command]
+pub fn list_memos(
+    app: tauri::AppHandle,
+    state: State<'_, VaultState>,
+    index: State<'_, IndexState>,
+    extensions: Option<Vec<String>>,
+    options: Option<ListMemosOptions>,
+    vault_id: Option<String>,
+) -> Result<MemoPage, CommandError> {
+    let (vault, path, settings) = {
+        let registry = state.lock().scoped(vault_id.as_deref())?;
+        (registry.active_vault()?.id.clone(), registry.active_folder()?, registry.active_settings()?)
+    };
+
+    let extensions = resolve_extensions(extensions, settings.extensions)?;
+    let modified = index::folder_modified(&path);
+    let mut memos = scan_memos(&path, &extensions)?;
+
+    // Pins are keyed by ID, so they only match once syncing has found memos renamed outside the app
+    let pinned = {
+        let guard = state.lock();
+        let registry = guard.scoped(vault_id.as_deref())?;
+        ids::sync_ids(&registry, &mut memos)?;
+        pins::pinned_ids(&registry)?
+    };
+    for memo in memos.iter_mut() {
+        memo.pinned = pinned.contains(&memo.id);
     }
+    // Lets quick open skip rescanning the folder until it changes
+    index.with(&vault, |index| index.set_listing(&path, &extensions, modified, &memos));
+    // Listing loaded the folder's config, so this is where a broken one gets reported
+    folder_config::report_errors(&app);
+
+    let options = options.unwrap_or_default();
+    let mut page = listing::paginate(memos, &options)?;
+    // Only read the contents of the memos on this page; summaries are cached by mtime
+    if options.include_preview || options.include_stats {
+        index.with(&vault, |index| {
+            for memo in page.memos.iter_mut() {
+                if let Some(summary) = index.summary(memo) {
+                    memo.preview = options.include_preview.then_some(summary.excerpt);
+                    memo.stats = options.include_stats.then_some(summary.stats);
+                }
+            }
+        });
+    }
+    Ok(page)
+}
 
-    // Security: Validate path is within working folder
-    if !is_within_folder(&folder, &path)? {
-        return Err(CommandError::AccessDenied(
-            "file is outside working folder".to_string(),
-        ));
+#[tauri::command]
+pub fn read_memo(
+    state: State<'_, VaultState>,
+    crypto: State<'_, CryptoState>,
+    file_path: String,
+    max_size: Option<u64>,
+    vault_id: Option<String>,
+) -> Result<MemoFile, CommandError> {
+    let folder = state.lock().scoped(vault_id.as_deref())?.active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, kind) = validate_memo_path(&folder, &file_path)?;
+
+    // Refuse to ship huge files over IPC in one piece; callers should page with read_memo_range.
+    // A caller may ask for less, but never for more than the default.
+    let size = get_file_size(&path);
+    let limit = max_size.map_or(DEFAULT_MAX_MEMO_SIZE, |max_size| max_size.min(DEFAULT_MAX_MEMO_SIZE));
+    if size > limit {
+        return Err(CommandError::MemoTooLarge(limit, size));
     }
 
-    let content = fs::read_to_string(&path)?;
-    let name = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("Untitled")
-        .to_string();
-    let (modified_at, created_at) = get_file_times(&path);
+    let mut bytes = fs::read(&path)?;
+    if crypto::is_encrypted(&path) {
+        bytes = crypto.decrypt(&folder, &bytes)?;
+    }
+    let decoded = encoding::decode(&bytes)?;
+    let mut meta = build_memo_meta(&path, kind);
+    meta.path = file_path;
+    ids::assign_id(&state.lock().scoped(vault_id.as_deref())?, &mut meta)?;
 
     Ok(MemoFile {
-        path: file_path,
-        name,
-        content,
-        modified_at,
-        created_at,
+        id: meta.id,
+        path: meta.path,
+        name: meta.name,
+        kind,
+        encrypted: meta.encrypted,
+        content: decoded.content,
+        encoding: decoded.format.encoding.name().to_string(),
+        has_bom: decoded.format.has_bom,
+        line_ending: decoded.format.line_ending,
+        size: meta.size,
+        modified_at: meta.modified_at,
+        created_at: meta.created_at,
     })
 }
 
+/// Reads part of a memo starting at a byte offset. Chunks end on line boundaries where
+/// possible, so `next_offset` should be passed back as the following `offset`.
 #[tauri::command]
-pub fn save_memo(file_path: String, content: String, working_folder: String) -> Result<MemoMeta, CommandError> {
-    let path = PathBuf::from(&file_path);
-    let folder = PathBuf::from(&working_folder);
-
-    // Security: Validate .md extension
-    if !is_markdown_file(&path) {
-        return Err(CommandError::NotMarkdownFile);
+pub fn read_memo_range(
+    state: State<'_, VaultState>,
+    file_path: String,
+    offset: u64,
+    len: u64,
+    vault_id: Option<String>,
+) -> Result<MemoChunk, CommandError> {
+    let folder = state.lock().scoped(vault_id.as_deref())?.active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, _) = validate_memo_path(&folder, &file_path)?;
+    // Encrypted memos can only be decrypted as a whole
+    if crypto::is_encrypted(&path) {
+        return Err(CommandError::MemoEncrypted);
     }
 
-    if !path.exists() {
-        return Err(CommandError::FileNotFound(file_path.clone()));
-    }
+    let mut file = fs::File::open(&path)?;
+    let size = file.metadata()?.len();
 
-    // Security: Validate path is within working folder
-    if !is_within_folder(&folder, &path)? {
-        return Err(CommandError::AccessDenied(
-            "file is outside working folder".to_string(),
-        ));
-    }
+    let mut head = Vec::new();
+    (&mut file).take(MAX_CHUNK_SIZE.min(size)).read_to_end(&mut head)?;
+    let (text_encoding, bom_len) = encoding::detect_sample_encoding(&head, head.len() as u64 == size);
 
-    fs::write(&path, &content)?;
+    // An odd offset into UTF-16 would decode every unit of the chunk off by a byte
+    let offset = encoding::align_offset(offset.max(bom_len as u64).min(size), text_encoding);
+    let len = len.clamp(1, MAX_CHUNK_SIZE);
+    let mut buffer = Vec::new();
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(len).read_to_end(&mut buffer)?;
 
-    let name = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("Untitled")
-        .to_string();
-    let (modified_at, created_at) = get_file_times(&path);
+    let is_last = offset + buffer.len() as u64 >= size;
+    let (content, consumed) = encoding::decode_chunk(&buffer, text_encoding, is_last)?;
+    let next_offset = offset + consumed as u64;
 
-    Ok(MemoMeta {
+    Ok(MemoChunk {
         path: file_path,
-        name,
-        modified_at,
-        created_at,
+        offset,
+        next_offset,
+        size,
+        content,
+        encoding: text_encoding.name().to_string(),
+        eof: next_offset >= size,
     })
 }
 
 #[tauri::command]
-pub fn create_memo(folder_path: String, file_name: String) -> Result<MemoMeta, CommandError> {
-    let folder = PathBuf::from(&folder_path);
-    if !folder.exists() || !folder.is_dir() {
-        return Err(CommandError::InvalidFolder(folder_path));
+#[allow(clippy::too_many_arguments)]
+pub fn save_memo(
+    state: State<'_, VaultState>,
+    git: State<'_, GitState>,
+    crypto: State<'_, CryptoState>,
+    autosave: State<'_, AutosaveState>,
+    file_path: String,
+    content: String,
+    encoding: Option<String>,
+    line_ending: Option<LineEnding>,
+    vault_id: Option<String>,
+) -> Result<MemoMeta, CommandError> {
+    let folder = state.lock().scoped(vault_id.as_deref())?.active_folder()?;
+    // An explicit save supersedes whatever is still queued for the memo
+    autosave.discard(&folder, &file_path);
+    let mut meta = write_memo(&folder, &crypto, &file_path, &content, encoding, line_ending)?;
+    if let Ok(registry) = state.lock().scoped(vault_id.as_deref()) {
+        record_save(&registry, &git, &mut meta);
+    }
+    Ok(meta)
+}
+
+/// Writes a memo of the working folder, keeping its encoding, line endings and encryption
+/// unless a conversion is requested. Shared by `save_memo` and the autosave queue.
+pub(crate) fn write_memo(
+    folder: &Path,
+    crypto: &CryptoState,
+    file_path: &str,
+    content: &str,
+    encoding: Option<String>,
+    line_ending: Option<LineEnding>,
+) -> Result<MemoMeta, CommandError> {
+    // Security: Validate extension and that the file is within the working folder
+    let (path, kind) = validate_memo_path(folder, file_path)?;
+
+    let encrypted = crypto::is_encrypted(&path);
+    let mut current = fs::read(&path)?;
+    if encrypted {
+        current = crypto.decrypt(folder, &current)?;
     }
+    // Keep the file's current encoding and line endings unless a conversion is requested
+    let mut format = encoding::detect_format(&current);
+    if let Some(label) = encoding {
+        let target = encoding::resolve_encoding(&label)?;
+        if target != format.encoding {
+            format.encoding = target;
+            format.has_bom = encoding::default_bom(target);
+        }
+    }
+    if let Some(line_ending) = line_ending {
+        format.line_ending = line_ending;
+    }
+
+    let mut bytes = encoding::encode(content, &format)?;
+    if encrypted {
+        bytes = crypto.encrypt(folder, &bytes)?;
+    }
+    fs::write(&path, bytes)?;
+
+    let mut meta = build_memo_meta(&path, kind);
+    meta.path = file_path.to_string();
+    Ok(meta)
+}
+
+/// Fills in the ID and pin state of a just-saved memo and queues it for auto-commit.
+/// Bookkeeping only: the memo is already saved, so failures aren't reported.
+pub(crate) fn record_save(registry: &VaultRegistry, git: &GitState, meta: &mut MemoMeta) {
+    let _ = ids::assign_id(registry, meta);
+    meta.pinned = pins::pinned_ids(registry).is_ok_and(|pinned| pinned.contains(&meta.id));
+    git.record_change(registry);
+}
+
+#[tauri::command]
+pub fn create_memo(
+    state: State<'_, VaultState>,
+    git: State<'_, GitState>,
+    file_name: String,
+    extension: Option<String>,
+    vault_id: Option<String>,
+) -> Result<MemoMeta, CommandError> {
+    let (folder, settings) = {
+        let registry = state.lock().scoped(vault_id.as_deref())?;
+        (registry.active_folder()?, registry.active_settings()?)
+    };
 
     // Security: Validate file_name doesn't contain path traversal
     if !is_safe_filename(&file_name) {
@@ -277,75 +654,68 @@ pub fn create_memo(folder_path: String, file_name: String) -> Result<MemoMeta, C
         base_name
     };
 
-    let mut file_path = folder.join(format!("{}.md", base_name));
+    let ext = normalize_extension(extension.as_deref().unwrap_or(&settings.default_extension))?;
+    let kind = MemoKind::from_extension(&ext).ok_or(CommandError::NotMemoFile)?;
+
+    let mut file_path = folder.join(format!("{}.{}", base_name, ext));
     let mut counter = 1;
     while file_path.exists() {
-        file_path = folder.join(format!("{}-{}.md", base_name, counter));
+        file_path = folder.join(format!("{}-{}.{}", base_name, counter, ext));
         counter += 1;
     }
 
     fs::write(&file_path, "")?;
 
-    let name = file_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("Untitled")
-        .to_string();
-    let (modified_at, created_at) = get_file_times(&file_path);
-
-    Ok(MemoMeta {
-        path: file_path.to_string_lossy().to_string(),
-        name,
-        modified_at,
-        created_at,
-    })
+    let mut meta = build_memo_meta(&file_path, kind);
+    let guard = state.lock();
+    let Ok(registry) = guard.scoped(vault_id.as_deref()) else {
+        return Ok(meta);
+    };
+    let _ = ids::assign_id(&registry, &mut meta);
+    git.record_change(&registry);
+    Ok(meta)
 }
 
 #[tauri::command]
-pub fn delete_memo(file_path: String, working_folder: String) -> Result<(), CommandError> {
-    let path = PathBuf::from(&file_path);
-    let folder = PathBuf::from(&working_folder);
-
-    // Security: Validate .md extension
-    if !is_markdown_file(&path) {
-        return Err(CommandError::NotMarkdownFile);
-    }
+pub fn delete_memo(
+    state: State<'_, VaultState>,
+    git: State<'_, GitState>,
+    autosave: State<'_, AutosaveState>,
+    file_path: String,
+    vault_id: Option<String>,
+) -> Result<(), CommandError> {
+    let folder = state.lock().scoped(vault_id.as_deref())?.active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, _) = validate_memo_path(&folder, &file_path)?;
 
-    if !path.exists() {
-        return Err(CommandError::FileNotFound(file_path));
-    }
+    autosave.discard(&folder, &file_path);
+    fs::remove_file(&path)?;
 
-    // Security: Validate path is within working folder
-    if !is_within_folder(&folder, &path)? {
-        return Err(CommandError::AccessDenied(
-            "file is outside working folder".to_string(),
-        ));
+    // Bookkeeping only: the memo is already gone, so stale recent/pin entries must not fail the delete
+    let guard = state.lock();
+    let Ok(registry) = guard.scoped(vault_id.as_deref()) else {
+        return Ok(());
+    };
+    let _ = session::forget_recent(&registry, &file_path);
+    if let Ok(Some(id)) = ids::forget_id(&registry, &file_path) {
+        let _ = pins::forget_pin(&registry, &id);
     }
-
-    fs::remove_file(&path)?;
+    git.record_change(&registry);
     Ok(())
 }
 
 #[tauri::command]
-pub fn rename_memo(file_path: String, new_name: String, working_folder: String) -> Result<MemoMeta, CommandError> {
-    let path = PathBuf::from(&file_path);
-    let folder = PathBuf::from(&working_folder);
-
-    // Security: Validate .md extension
-    if !is_markdown_file(&path) {
-        return Err(CommandError::NotMarkdownFile);
-    }
-
-    if !path.exists() {
-        return Err(CommandError::FileNotFound(file_path.clone()));
-    }
-
-    // Security: Validate path is within working folder
-    if !is_within_folder(&folder, &path)? {
-        return Err(CommandError::AccessDenied(
-            "file is outside working folder".to_string(),
-        ));
-    }
+pub fn rename_memo(
+    state: State<'_, VaultState>,
+    git: State<'_, GitState>,
+    autosave: State<'_, AutosaveState>,
+    file_path: String,
+    new_name: String,
+    vault_id: Option<String>,
+) -> Result<MemoMeta, CommandError> {
+    let folder = state.lock().scoped(vault_id.as_deref())?.active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, kind) = validate_memo_path(&folder, &file_path)?;
 
     // Security: Validate new_name doesn't contain path traversal
     if !is_safe_filename(&new_name) {
@@ -357,32 +727,45 @@ pub fn rename_memo(file_path: String, new_name: String, working_folder: String)
         return Err(CommandError::InvalidFileName("Name cannot be empty".to_string()));
     }
 
-    // Build new file path
-    let new_file_path = folder.join(format!("{}.md", new_name));
+    // Build new file path, keeping the original extension as-is (`.md.age` for encrypted memos)
+    let plain_path = crypto::plaintext_path(&path);
+    let ext = plain_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or(DEFAULT_MEMO_EXTENSION);
+    let new_file_path = if crypto::is_encrypted(&path) {
+        folder.join(format!("{}.{}.{}", new_name, ext, crypto::ENCRYPTED_EXTENSION))
+    } else {
+        folder.join(format!("{}.{}", new_name, ext))
+    };
 
     // Check if new path already exists (and is not the same file)
     if new_file_path.exists() && new_file_path.canonicalize()? != path.canonicalize()? {
         return Err(CommandError::InvalidFileName(format!("File '{}' already exists", new_name)));
     }
 
-    // Rename the file
+    // Rename the file, after writing any queued autosave to its current path
+    autosave.flush_memo(&folder, &file_path);
     fs::rename(&path, &new_file_path)?;
 
-    let (modified_at, created_at) = get_file_times(&new_file_path);
-
-    Ok(MemoMeta {
-        path: new_file_path.to_string_lossy().to_string(),
-        name: new_name.to_string(),
-        modified_at,
-        created_at,
-    })
+    let mut meta = build_memo_meta(&new_file_path, kind);
+    // Bookkeeping only: the rename already happened, so don't report it as failed
+    let guard = state.lock();
+    let Ok(registry) = guard.scoped(vault_id.as_deref()) else {
+        return Ok(meta);
+    };
+    let _ = session::rename_recent(&registry, &file_path, &meta.path);
+    let _ = ids::rename_id(&registry, &file_path, &meta.path);
+    let _ = ids::assign_id(&registry, &mut meta);
+    meta.pinned = pins::pinned_ids(&registry).is_ok_and(|pinned| pinned.contains(&meta.id));
+    git.record_change(&registry);
+    Ok(meta)
 }
 
 // ============================================================
 // Font Management Commands
 // ============================================================
 
-const ALLOWED_FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "woff", "woff2"];
 const MAX_FONT_SIZE_MB: u64 = 50;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -392,6 +775,15 @@ pub struct InstalledFont {
     pub filename: String,
     pub format: String,
     pub installed_at: u64,
+    /// Hex SHA-256 of the file; installing the same file again reuses this entry
+    pub sha256: String,
+    /// Read from the font's `name` table
+    pub family: String,
+    pub style: String,
+    pub weight: u16,
+    pub italic: bool,
+    /// Scripts the font covers, e.g. `["latin", "han", "japanese"]`
+    pub scripts: Vec<String>,
 }
 
 /// Opens a file dialog to pick a font file
@@ -400,7 +792,7 @@ pub async fn pick_font_file(app: tauri::AppHandle) -> Result<Option<String>, Com
     let (tx, rx) = oneshot::channel();
     app.dialog()
         .file()
-        .add_filter("Font Files", &ALLOWED_FONT_EXTENSIONS)
+        .add_filter("Font Files", &fonts::FONT_FORMATS)
         .pick_file(move |file_path| {
             let _ = tx.send(file_path);
         });
@@ -412,12 +804,15 @@ pub async fn pick_font_file(app: tauri::AppHandle) -> Result<Option<String>, Com
     }
 }
 
-/// Installs a font file by copying it to the app data directory
+/// Installs a font file by copying it to the app data directory. The file must parse as a
+/// font; its format is taken from the content, and the label defaults to the family name.
+/// Installing a file that is already installed returns the existing entry.
 #[tauri::command]
 pub async fn install_font(
     app: tauri::AppHandle,
+    font_state: State<'_, FontState>,
     font_file_path: String,
-    label: String,
+    label: Option<String>,
 ) -> Result<InstalledFont, CommandError> {
     let source_path = PathBuf::from(&font_file_path);
 
@@ -426,17 +821,6 @@ pub async fn install_font(
         return Err(CommandError::FileNotFound(font_file_path));
     }
 
-    // Validate extension
-    let ext = source_path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-
-    if !ALLOWED_FONT_EXTENSIONS.contains(&ext.as_str()) {
-        return Err(CommandError::UnsupportedFontFormat(ext));
-    }
-
     // Validate file size
     let metadata = fs::metadata(&source_path)?;
     let size_mb = metadata.len() / (1024 * 1024);
@@ -444,76 +828,129 @@ pub async fn install_font(
         return Err(CommandError::FileTooLarge(MAX_FONT_SIZE_MB, size_mb));
     }
 
-    // Get app data directory and create fonts subdirectory
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| CommandError::PathError(e.to_string()))?;
-    let fonts_dir = app_data_dir.join("fonts");
-    fs::create_dir_all(&fonts_dir)?;
-
-    // Generate unique filename using UUID
-    let unique_id = uuid::Uuid::new_v4().to_string();
-    let target_filename = format!("{}.{}", unique_id, ext);
-    let target_path = fonts_dir.join(&target_filename);
+    // Validate the content rather than the extension; the bytes checked are the bytes installed
+    let data = fs::read(&source_path)?;
+    let font = fonts::font_entry(uuid::Uuid::new_v4().to_string(), label, &data, now_millis())?;
 
-    // Copy file to fonts directory
-    fs::copy(&source_path, &target_path)?;
+    let _guard = font_state.lock();
+    let fonts_dir = fonts::fonts_dir(&app)?;
+    let mut installed = fonts::load_fonts(&fonts_dir)?;
+    if let Some(existing) = installed.iter().find(|existing| existing.sha256 == font.sha256) {
+        return Ok(existing.clone());
+    }
 
-    let installed_at = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| d.as_millis() as u64)
-        .unwrap_or(0);
+    // Copy file to fonts directory, named by UUID so the original file name never reaches the path
+    fs::write(fonts_dir.join(&font.filename), &data)?;
+    installed.push(font.clone());
+    fonts::save_fonts(&fonts_dir, installed)?;
+    Ok(font)
+}
 
-    Ok(InstalledFont {
-        id: unique_id,
-        label,
-        filename: target_filename,
-        format: ext,
-        installed_at,
-    })
+/// Lists the installed fonts, repairing the manifest against the files on disk first
+#[tauri::command]
+pub async fn list_installed_fonts(
+    app: tauri::AppHandle,
+    font_state: State<'_, FontState>,
+) -> Result<Vec<InstalledFont>, CommandError> {
+    let _guard = font_state.lock();
+    fonts::load_fonts(&fonts::fonts_dir(&app)?)
 }
 
-/// Gets the full path to an installed font file for loading in the frontend
+/// Looks up an installed font. The frontend loads the file itself from
+/// `memofont://localhost/<id>` rather than from a filesystem path.
 #[tauri::command]
-pub async fn get_installed_font_path(
+pub async fn get_installed_font(
     app: tauri::AppHandle,
+    font_state: State<'_, FontState>,
     font_id: String,
-    format: String,
-) -> Result<String, CommandError> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| CommandError::PathError(e.to_string()))?;
-    let fonts_dir = app_data_dir.join("fonts");
-    let font_filename = format!("{}.{}", font_id, format);
-    let font_path = fonts_dir.join(&font_filename);
-
-    if !font_path.exists() {
-        return Err(CommandError::FileNotFound(font_filename));
-    }
-
-    Ok(font_path.to_string_lossy().to_string())
+) -> Result<InstalledFont, CommandError> {
+    let _guard = font_state.lock();
+    fonts::registered_font(&fonts::fonts_dir(&app)?, &font_id)
 }
 
-/// Deletes an installed font file
+/// Deletes an installed font file and its manifest entry
 #[tauri::command]
 pub async fn delete_installed_font(
     app: tauri::AppHandle,
+    font_state: State<'_, FontState>,
     font_id: String,
-    format: String,
 ) -> Result<(), CommandError> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| CommandError::PathError(e.to_string()))?;
-    let fonts_dir = app_data_dir.join("fonts");
-    let font_filename = format!("{}.{}", font_id, format);
-    let font_path = fonts_dir.join(&font_filename);
+    let _guard = font_state.lock();
+    let fonts_dir = fonts::fonts_dir(&app)?;
+    // Security: only files the registry knows about are deleted
+    let font = fonts::registered_font(&fonts_dir, &font_id)?;
 
+    let font_path = fonts_dir.join(&font.filename);
     if font_path.exists() {
         fs::remove_file(&font_path)?;
     }
 
+    let mut installed = fonts::load_fonts(&fonts_dir)?;
+    installed.retain(|font| font.id != font_id);
+    fonts::save_fonts(&fonts_dir, installed)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixture {
+        root: PathBuf,
+        folder: PathBuf,
+    }
+
+    impl Fixture {
+        /// A working folder `notes/` holding `memo.md`, next to `outside.md`
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!("memo-commands-test-{}", uuid::Uuid::new_v4()));
+            let folder = root.join("notes");
+            fs::create_dir_all(&folder).unwrap();
+            fs::write(folder.join("memo.md"), "inside").unwrap();
+            fs::write(root.join("outside.md"), "outside").unwrap();
+            Fixture { root, folder }
+        }
+
+        fn validate(&self, file_path: &Path) -> Result<(PathBuf, MemoKind), CommandError> {
+            validate_memo_path(&self.folder, &file_path.to_string_lossy())
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    #[test]
+    fn memos_inside_the_folder_are_accepted() {
+        let fixture = Fixture::new();
+        let (path, kind) = fixture.validate(&fixture.folder.join("memo.md")).unwrap();
+        assert_eq!(path, fixture.folder.join("memo.md"));
+        assert_eq!(kind, MemoKind::Markdown);
+    }
+
+    #[test]
+    fn parent_traversal_is_rejected() {
+        let fixture = Fixture::new();
+        let result = fixture.validate(&fixture.folder.join("..").join("outside.md"));
+        assert!(matches!(result, Err(CommandError::AccessDenied(_))));
+    }
+
+    #[test]
+    fn absolute_paths_outside_the_folder_are_rejected() {
+        let fixture = Fixture::new();
+        let result = fixture.validate(&fixture.root.join("outside.md"));
+        assert!(matches!(result, Err(CommandError::AccessDenied(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_escaping_the_folder_are_rejected() {
+        let fixture = Fixture::new();
+        let link = fixture.folder.join("link.md");
+        std::os::unix::fs::symlink(fixture.root.join("outside.md"), &link).unwrap();
+        let result = fixture.validate(&link);
+        assert!(matches!(result, Err(CommandError::AccessDenied(_))));
+    }
+}
@@ -0,0 +1,211 @@
+use age::secrecy::{ExposeSecret, SecretString};
+use age::{scrypt, x25519};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::State;
+
+use crate::autosave::AutosaveState;
+use crate::commands::{build_memo_meta, validate_memo_path, CommandError, MemoMeta};
+use crate::git::GitState;
+use crate::vault::VaultState;
+use crate::{ids, pins, session};
+
+/// Encrypted memos keep their memo extension in front of this one: `secrets.md.age`
+pub const ENCRYPTED_EXTENSION: &str = "age";
+/// The vault's key, itself encrypted with the passphrase. Kept in the working folder so the
+/// encrypted memos can be opened wherever the folder is synced to.
+pub const KEY_FILE: &str = ".memo-key.age";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EncryptionStatus {
+    /// Whether the working folder has a key yet; the first unlock creates it (with `confirm`)
+    pub configured: bool,
+    pub unlocked: bool,
+}
+
+/// Managed state holding the unlocked keys, per working folder, for this session only.
+/// Keys never leave the backend.
+#[derive(Default)]
+pub struct CryptoState(Mutex<HashMap<PathBuf, x25519::Identity>>);
+
+impl CryptoState {
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, x25519::Identity>> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn identity(&self, folder: &Path) -> Result<x25519::Identity, CommandError> {
+        self.lock().get(folder).cloned().ok_or(CommandError::EncryptionLocked)
+    }
+
+    pub fn encrypt(&self, folder: &Path, plaintext: &[u8]) -> Result<Vec<u8>, CommandError> {
+        let recipient = self.identity(folder)?.to_public();
+        age::encrypt(&recipient, plaintext).map_err(|e| CommandError::EncryptionError(e.to_string()))
+    }
+
+    pub fn decrypt(&self, folder: &Path, ciphertext: &[u8]) -> Result<Vec<u8>, CommandError> {
+        age::decrypt(&self.identity(folder)?, ciphertext).map_err(|e| CommandError::EncryptionError(e.to_string()))
+    }
+}
+
+pub fn is_encrypted(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(ENCRYPTED_EXTENSION))
+}
+
+/// The memo an encrypted file stands for (`secrets.md.age` → `secrets.md`); other paths as-is
+pub fn plaintext_path(path: &Path) -> PathBuf {
+    if is_encrypted(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn status(crypto: &CryptoState, folder: &Path) -> EncryptionStatus {
+    EncryptionStatus {
+        configured: folder.join(KEY_FILE).is_file(),
+        unlocked: crypto.lock().contains_key(folder),
+    }
+}
+
+/// Opens the folder's key with the passphrase. Without a key yet, a new one is created, but
+/// only with the passphrase typed twice: a typo would otherwise lock the memos away for good.
+fn open_key(folder: &Path, passphrase: SecretString, confirm: Option<SecretString>) -> Result<x25519::Identity, CommandError> {
+    let key_file = folder.join(KEY_FILE);
+    if !key_file.exists() {
+        let Some(confirm) = confirm else {
+            return Err(CommandError::EncryptionError("no key yet; confirm the new passphrase to create one".to_string()));
+        };
+        if confirm.expose_secret() != passphrase.expose_secret() {
+            return Err(CommandError::EncryptionError("passphrases don't match".to_string()));
+        }
+        let identity = x25519::Identity::generate();
+        let wrapped = age::encrypt(&scrypt::Recipient::new(passphrase), identity.to_string().expose_secret().as_bytes())
+            .map_err(|e| CommandError::EncryptionError(e.to_string()))?;
+        // A key that appeared meanwhile (another window, a sync) must not be replaced
+        fs::OpenOptions::new().write(true).create_new(true).open(&key_file)?.write_all(&wrapped)?;
+        return Ok(identity);
+    }
+
+    let key = age::decrypt(&scrypt::Identity::new(passphrase), &fs::read(&key_file)?)
+        .map_err(|_| CommandError::EncryptionError("wrong passphrase".to_string()))?;
+    let key = SecretString::from(String::from_utf8_lossy(&key).to_string());
+    key.expose_secret()
+        .trim()
+        .parse()
+        .map_err(|e: &str| CommandError::EncryptionError(e.to_string()))
+}
+
+#[tauri::command]
+pub fn encryption_status(state: State<'_, VaultState>, crypto: State<'_, CryptoState>) -> Result<EncryptionStatus, CommandError> {
+    let folder = state.lock().active_folder()?;
+    Ok(status(&crypto, &folder))
+}
+
+/// Unlocks the encrypted memos of the working folder until `lock_encryption` or the app quits.
+/// The first unlock sets the passphrase and needs it repeated in `confirm`; later ones ignore it.
+#[tauri::command]
+pub async fn unlock_encryption(
+    state: State<'_, VaultState>,
+    crypto: State<'_, CryptoState>,
+    passphrase: String,
+    confirm: Option<String>,
+) -> Result<EncryptionStatus, CommandError> {
+    if passphrase.is_empty() {
+        return Err(CommandError::EncryptionError("passphrase is empty".to_string()));
+    }
+    let folder = state.lock().active_folder()?;
+    // Passphrase hashing deliberately takes a second or more, so keep it off the main thread
+    let key_folder = folder.clone();
+    let identity = tauri::async_runtime::spawn_blocking(move || {
+        open_key(&key_folder, SecretString::from(passphrase), confirm.map(SecretString::from))
+    })
+        .await
+        .map_err(|e| CommandError::EncryptionError(e.to_string()))??;
+    crypto.lock().insert(folder.clone(), identity);
+    Ok(status(&crypto, &folder))
+}
+
+/// Forgets the working folder's key; encrypted memos can't be opened until unlocked again
+#[tauri::command]
+pub fn lock_encryption(state: State<'_, VaultState>, crypto: State<'_, CryptoState>) -> Result<EncryptionStatus, CommandError> {
+    let folder = state.lock().active_folder()?;
+    crypto.lock().remove(&folder);
+    Ok(status(&crypto, &folder))
+}
+
+/// Moves recent/ID bookkeeping from the old file to the new one; the pin follows the ID
+fn carry_over(state: &VaultState, git: &GitState, old_path: &str, meta: &mut MemoMeta) {
+    // Bookkeeping only: the file is already replaced
+    let registry = state.lock();
+    let _ = session::rename_recent(&registry, old_path, &meta.path);
+    let _ = ids::rename_id(&registry, old_path, &meta.path);
+    let _ = ids::assign_id(&registry, meta);
+    meta.pinned = pins::pinned_ids(&registry).is_ok_and(|pinned| pinned.contains(&meta.id));
+    git.record_change(&registry);
+}
+
+/// Replaces a memo with an encrypted copy (`memo.md` → `memo.md.age`). The plaintext file is
+/// deleted, but earlier copies of it (backups, git history, synced folders) are not.
+#[tauri::command]
+pub fn encrypt_memo(
+    state: State<'_, VaultState>,
+    git: State<'_, GitState>,
+    crypto: State<'_, CryptoState>,
+    autosave: State<'_, AutosaveState>,
+    file_path: String,
+) -> Result<MemoMeta, CommandError> {
+    let folder = state.lock().active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, kind) = validate_memo_path(&folder, &file_path)?;
+    if is_encrypted(&path) {
+        return Err(CommandError::MemoEncrypted);
+    }
+
+    let encrypted_path = PathBuf::from(format!("{}.{}", file_path, ENCRYPTED_EXTENSION));
+    if encrypted_path.exists() {
+        return Err(CommandError::InvalidFileName(format!("File '{}' already exists", encrypted_path.display())));
+    }
+    // The file is replaced, so write any queued autosave to it first
+    autosave.flush_memo(&folder, &file_path);
+    fs::write(&encrypted_path, crypto.encrypt(&folder, &fs::read(&path)?)?)?;
+    fs::remove_file(&path)?;
+
+    let mut meta = build_memo_meta(&encrypted_path, kind);
+    carry_over(&state, &git, &file_path, &mut meta);
+    Ok(meta)
+}
+
+/// Turns an encrypted memo back into a plain one (`memo.md.age` → `memo.md`)
+#[tauri::command]
+pub fn decrypt_memo(
+    state: State<'_, VaultState>,
+    git: State<'_, GitState>,
+    crypto: State<'_, CryptoState>,
+    autosave: State<'_, AutosaveState>,
+    file_path: String,
+) -> Result<MemoMeta, CommandError> {
+    let folder = state.lock().active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, kind) = validate_memo_path(&folder, &file_path)?;
+    if !is_encrypted(&path) {
+        return Err(CommandError::NotEncrypted);
+    }
+
+    let plain_path = plaintext_path(&path);
+    if plain_path.exists() {
+        return Err(CommandError::InvalidFileName(format!("File '{}' already exists", plain_path.display())));
+    }
+    // The file is replaced, so write any queued autosave to it first
+    autosave.flush_memo(&folder, &file_path);
+    fs::write(&plain_path, crypto.decrypt(&folder, &fs::read(&path)?)?)?;
+    fs::remove_file(&path)?;
+
+    let mut meta = build_memo_meta(&plain_path, kind);
+    carry_over(&state, &git, &file_path, &mut meta);
+    Ok(meta)
+}
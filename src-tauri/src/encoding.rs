@@ -0,0 +1,340 @@
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::{Encoding, REPLACEMENT, UTF_16BE, UTF_16LE, UTF_8};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::CommandError;
+
+/// Number of leading bytes inspected when guessing a legacy encoding
+const DETECTION_SAMPLE_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+/// How a memo is stored on disk
+#[derive(Debug, Clone, Copy)]
+pub struct TextFormat {
+    pub encoding: &'static Encoding,
+    pub has_bom: bool,
+    pub line_ending: LineEnding,
+}
+
+/// Memo content normalized to LF, together with the format it was stored in
+#[derive(Debug)]
+pub struct DecodedText {
+    pub content: String,
+    pub format: TextFormat,
+}
+
+/// Looks up an encoding by its WHATWG label (e.g. "shift_jis", "euc-jp", "utf-16le")
+pub fn resolve_encoding(label: &str) -> Result<&'static Encoding, CommandError> {
+    match Encoding::for_label(label.trim().as_bytes()) {
+        Some(encoding) if encoding != REPLACEMENT => Ok(encoding),
+        _ => Err(CommandError::UnsupportedEncoding(label.to_string())),
+    }
+}
+
+/// Whether files in this encoding are written with a BOM by default
+pub fn default_bom(encoding: &'static Encoding) -> bool {
+    encoding == UTF_16LE || encoding == UTF_16BE
+}
+
+/// Detects the encoding of raw file bytes, returning it with the BOM length
+pub fn detect_encoding(bytes: &[u8]) -> (&'static Encoding, usize) {
+    detect_sample_encoding(bytes, true)
+}
+
+/// Like `detect_encoding`, but tolerates a sample cut off in the middle of a character
+pub fn detect_sample_encoding(bytes: &[u8], is_complete: bool) -> (&'static Encoding, usize) {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        return (encoding, bom_len);
+    }
+    // NUL bytes are valid UTF-8, so BOM-less UTF-16 has to be ruled out first
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return (encoding, 0);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => return (UTF_8, 0),
+        Err(e) if !is_complete && e.error_len().is_none() => return (UTF_8, 0),
+        Err(_) => {}
+    }
+
+    let sample = &bytes[..bytes.len().min(DETECTION_SAMPLE_SIZE)];
+    let mut detector = EncodingDetector::new(Iso2022JpDetection::Allow);
+    detector.feed(sample, is_complete && sample.len() == bytes.len());
+    // Most legacy memos come from Japanese Windows tools, so bias ties towards Japanese encodings
+    (detector.guess(Some(b"jp"), Utf8Detection::Deny), 0)
+}
+
+/// Recognizes BOM-less UTF-16 by the zero high bytes of ASCII characters
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if sample.is_empty() {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let even_zeros = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_zeros = sample.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+
+    if odd_zeros * 10 >= pairs * 4 && even_zeros * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even_zeros * 10 >= pairs * 4 && odd_zeros * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Picks CRLF when it is the dominant line terminator
+pub fn detect_line_ending(text: &str) -> LineEnding {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    if crlf > lf {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Lf
+    }
+}
+
+/// Decodes memo bytes, failing instead of silently replacing malformed sequences
+pub fn decode(bytes: &[u8]) -> Result<DecodedText, CommandError> {
+    let (encoding, bom_len) = detect_encoding(bytes);
+    let text = encoding
+        .decode_without_bom_handling_and_without_replacement(&bytes[bom_len..])
+        .ok_or_else(|| CommandError::InvalidEncoding(encoding.name().to_string()))?;
+    let line_ending = detect_line_ending(&text);
+
+    Ok(DecodedText {
+        content: text.replace("\r\n", "\n"),
+        format: TextFormat {
+            encoding,
+            has_bom: bom_len > 0,
+            line_ending,
+        },
+    })
+}
+
+/// Decodes the longest prefix of a chunk that ends on a character boundary, preferring
+/// to stop after the last complete line. Returns the LF-normalized text and bytes consumed.
+pub fn decode_chunk(
+    bytes: &[u8],
+    encoding: &'static Encoding,
+    is_last: bool,
+) -> Result<(String, usize), CommandError> {
+    let is_utf16 = encoding == UTF_16LE || encoding == UTF_16BE;
+    let step = if is_utf16 { 2 } else { 1 };
+
+    let mut end = bytes.len();
+    if !is_last {
+        if is_utf16 {
+            end &= !1;
+        } else if encoding.is_ascii_compatible() {
+            // A LF byte never occurs inside a multi-byte character in these encodings
+            if let Some(pos) = bytes.iter().rposition(|b| *b == b'\n') {
+                end = pos + 1;
+            }
+        }
+    }
+
+    // Back off over a character split by the chunk boundary (a UTF-8 sequence or surrogate pair)
+    let min_end = if is_last { end } else { end.saturating_sub(4) };
+    let mut cut = end;
+    loop {
+        if let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(&bytes[..cut]) {
+            return Ok((text.replace("\r\n", "\n"), cut));
+        }
+        if cut < min_end + step {
+            return Err(CommandError::InvalidEncoding(encoding.name().to_string()));
+        }
+        cut -= step;
+    }
+}
+
+/// Moves a byte offset back to the start of a code unit, so a chunk read from it decodes
+/// in step with the file. UTF-16 units are two bytes; other encodings resynchronize on
+/// their own (see `decode_chunk`).
+pub fn align_offset(offset: u64, encoding: &'static Encoding) -> u64 {
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        offset & !1
+    } else {
+        offset
+    }
+}
+
+/// Detects the format of an existing file without requiring it to decode cleanly
+pub fn detect_format(bytes: &[u8]) -> TextFormat {
+    let (encoding, bom_len) = detect_encoding(bytes);
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    TextFormat {
+        encoding,
+        has_bom: bom_len > 0,
+        line_ending: detect_line_ending(&text),
+    }
+}
+
+/// Encodes memo content for writing, applying the requested line endings
+pub fn encode(content: &str, format: &TextFormat) -> Result<Vec<u8>, CommandError> {
+    let normalized = content.replace("\r\n", "\n");
+    let text = match format.line_ending {
+        LineEnding::Lf => normalized,
+        LineEnding::Crlf => normalized.replace('\n', "\r\n"),
+    };
+
+    let encoding = format.encoding;
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let little_endian = encoding == UTF_16LE;
+        let mut bytes = Vec::with_capacity(text.len() * 2 + 2);
+        let units = format.has_bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16());
+        for unit in units {
+            let pair = if little_endian {
+                unit.to_le_bytes()
+            } else {
+                unit.to_be_bytes()
+            };
+            bytes.extend_from_slice(&pair);
+        }
+        return Ok(bytes);
+    }
+
+    let (encoded, _, had_errors) = encoding.encode(&text);
+    if had_errors {
+        return Err(CommandError::UnencodableContent(encoding.name().to_string()));
+    }
+
+    let mut bytes = Vec::with_capacity(encoded.len() + 3);
+    if format.has_bom && encoding == UTF_8 {
+        bytes.extend_from_slice(b"\xEF\xBB\xBF");
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::SHIFT_JIS;
+
+    const TEXT: &str = "メモ: 日本語とEnglishの混在\n二行目の本文です。\n";
+
+    fn format(encoding: &'static Encoding, has_bom: bool, line_ending: LineEnding) -> TextFormat {
+        TextFormat { encoding, has_bom, line_ending }
+    }
+
+    /// Encodes `text`, checks it decodes back to the same text and format, and returns the bytes
+    fn round_trip(text: &str, format: TextFormat) -> Vec<u8> {
+        let bytes = encode(text, &format).unwrap();
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.content, text);
+        assert_eq!(decoded.format.encoding, format.encoding);
+        assert_eq!(decoded.format.has_bom, format.has_bom);
+        assert_eq!(decoded.format.line_ending, format.line_ending);
+        assert_eq!(encode(&decoded.content, &decoded.format).unwrap(), bytes);
+        bytes
+    }
+
+    #[test]
+    fn utf8_round_trips_with_and_without_bom() {
+        assert_eq!(round_trip(TEXT, format(UTF_8, false, LineEnding::Lf)), TEXT.as_bytes());
+        let with_bom = round_trip(TEXT, format(UTF_8, true, LineEnding::Lf));
+        assert_eq!(&with_bom[..3], b"\xEF\xBB\xBF");
+        assert_eq!(&with_bom[3..], TEXT.as_bytes());
+    }
+
+    #[test]
+    fn utf16_round_trips_in_both_byte_orders() {
+        assert_eq!(&round_trip(TEXT, format(UTF_16LE, true, LineEnding::Lf))[..2], b"\xFF\xFE");
+        assert_eq!(&round_trip(TEXT, format(UTF_16BE, true, LineEnding::Lf))[..2], b"\xFE\xFF");
+        // Without a BOM the byte order is told from the zero bytes of mostly ASCII text
+        let text = "# Notes\nMostly English, with some 日本語.\n";
+        round_trip(text, format(UTF_16LE, false, LineEnding::Crlf));
+        round_trip(text, format(UTF_16BE, false, LineEnding::Lf));
+    }
+
+    #[test]
+    fn shift_jis_is_detected_without_a_hint() {
+        let bytes = round_trip(TEXT, format(SHIFT_JIS, false, LineEnding::Crlf));
+        assert!(std::str::from_utf8(&bytes).is_err());
+        assert_eq!(detect_format(&bytes).encoding, SHIFT_JIS);
+    }
+
+    #[test]
+    fn line_endings_are_normalized_and_restored() {
+        let crlf = round_trip(TEXT, format(UTF_8, false, LineEnding::Crlf));
+        assert_eq!(crlf, TEXT.replace('\n', "\r\n").as_bytes());
+        // The dominant terminator wins; content always comes back with LF
+        let mixed = decode(b"a\r\nb\r\nc\n").unwrap();
+        assert_eq!(mixed.content, "a\nb\nc\n");
+        assert_eq!(mixed.format.line_ending, LineEnding::Crlf);
+        assert_eq!(decode(b"a\nb\r\nc\n").unwrap().format.line_ending, LineEnding::Lf);
+    }
+
+    #[test]
+    fn decode_chunk_stops_at_a_line_or_character_boundary() {
+        let bytes = "一行目\n二行目".as_bytes();
+        // Ends after the last complete line...
+        assert_eq!(decode_chunk(&bytes[..bytes.len() - 1], UTF_8, false).unwrap(), ("一行目\n".to_string(), 10));
+        // ...or, without one, before the character the chunk cuts through
+        let bytes = "あいう".as_bytes();
+        assert_eq!(decode_chunk(&bytes[..7], UTF_8, false).unwrap(), ("あい".to_string(), 6));
+        assert!(decode_chunk(&bytes[..7], UTF_8, true).is_err());
+        // A surrogate pair split in UTF-16 backs off over the whole pair
+        let bytes = encode("a😀", &format(UTF_16LE, false, LineEnding::Lf)).unwrap();
+        assert_eq!(decode_chunk(&bytes[..5], UTF_16LE, false).unwrap(), ("a".to_string(), 2));
+    }
+
+    /// Reads `bytes` the way `read_memo_range` pages through a file, `len` bytes at a time
+    fn read_in_chunks(bytes: &[u8], encoding: &'static Encoding, len: usize) -> String {
+        let (mut text, mut offset) = (String::new(), 0);
+        while offset < bytes.len() {
+            let end = (offset + len).min(bytes.len());
+            let (chunk, consumed) = decode_chunk(&bytes[offset..end], encoding, end == bytes.len()).unwrap();
+            assert!(consumed > 0, "chunk of {} bytes at {} made no progress", len, offset);
+            text.push_str(&chunk);
+            offset += consumed;
+        }
+        text
+    }
+
+    #[test]
+    fn chunks_cut_mid_character_resume_at_the_next_one() {
+        // Without line breaks every chunk boundary falls somewhere inside the text
+        let text = "日本語のメモとASCIIの混在";
+        for encoding in [UTF_8, SHIFT_JIS, UTF_16LE, UTF_16BE] {
+            let bytes = encode(text, &format(encoding, false, LineEnding::Lf)).unwrap();
+            for len in 5..=bytes.len() {
+                assert_eq!(read_in_chunks(&bytes, encoding, len), text, "{} in {}-byte chunks", encoding.name(), len);
+            }
+        }
+        // Four-byte UTF-8 sequences and UTF-16 surrogate pairs
+        let text = "絵文字😀の😀メモ";
+        for encoding in [UTF_8, UTF_16LE] {
+            let bytes = encode(text, &format(encoding, false, LineEnding::Lf)).unwrap();
+            for len in 5..=bytes.len() {
+                assert_eq!(read_in_chunks(&bytes, encoding, len), text, "{} in {}-byte chunks", encoding.name(), len);
+            }
+        }
+
+        // A Shift_JIS chunk ending on the lead byte of 本 stops before it
+        let bytes = encode("日本語", &format(SHIFT_JIS, false, LineEnding::Lf)).unwrap();
+        assert_eq!(decode_chunk(&bytes[..3], SHIFT_JIS, false).unwrap(), ("日".to_string(), 2));
+        // A UTF-8 chunk ending inside the four bytes of 😀 stops before it
+        let bytes = "a😀".as_bytes();
+        for end in 2..5 {
+            assert_eq!(decode_chunk(&bytes[..end], UTF_8, false).unwrap(), ("a".to_string(), 1));
+        }
+    }
+
+    #[test]
+    fn utf16_offsets_are_aligned_to_code_units() {
+        let bytes = encode("メモ", &format(UTF_16LE, true, LineEnding::Lf)).unwrap();
+        assert_eq!(align_offset(3, UTF_16LE), 2);
+        assert_eq!(align_offset(3, UTF_16BE), 2);
+        assert_eq!(align_offset(3, UTF_8), 3);
+        // Reading from the aligned offset starts at the first character after the BOM
+        let offset = align_offset(3, UTF_16LE) as usize;
+        assert_eq!(decode_chunk(&bytes[offset..], UTF_16LE, true).unwrap(), ("メモ".to_string(), 4));
+    }
+}
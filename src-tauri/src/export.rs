@@ -0,0 +1,174 @@
+use base64::Engine;
+use pulldown_cmark::{html, Options, Parser};
+use std::fs;
+use std::path::PathBuf;
+use tauri::State;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+
+use crate::commands::{build_memo_meta, validate_memo_path, CommandError, MemoKind};
+use crate::crypto::{self, CryptoState};
+use crate::encoding;
+use crate::fonts::{self, FontState};
+use crate::frontmatter;
+use crate::pdf;
+use crate::vault::VaultState;
+
+/// `font-family` the embedded font is declared under
+const EXPORT_FONT_FAMILY: &str = "MemoExportFont";
+
+const STYLESHEET: &str = "body { max-width: 46em; margin: 2em auto; padding: 0 1em; line-height: 1.7; }
+pre, code { font-family: ui-monospace, monospace; }
+pre { white-space: pre-wrap; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; }
+img { max-width: 100%; }";
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reads an installed font's file
+fn installed_font_data(font_state: &FontState, app: &tauri::AppHandle, font_id: &str) -> Result<Vec<u8>, CommandError> {
+    let _guard = font_state.lock();
+    let dir = fonts::fonts_dir(app)?;
+    let font = fonts::registered_font(&dir, font_id)?;
+    Ok(fs::read(dir.join(&font.filename))?)
+}
+
+/// `@font-face` for an installed font, cut down to the characters of `text`. Fonts the subsetter
+/// can't handle (WOFF2 with transformed outlines) are an error rather than embedded whole.
+fn font_face(font_state: &FontState, app: &tauri::AppHandle, font_id: &str, text: &str) -> Result<String, CommandError> {
+    let data = fonts::subset_font(&installed_font_data(font_state, app, font_id)?, text)?;
+    Ok(format!(
+        "@font-face {{ font-family: \"{}\"; src: url(data:font/woff;base64,{}) format(\"woff\"); }}\nbody {{ font-family: \"{}\", sans-serif; }}",
+        EXPORT_FONT_FAMILY,
+        base64::engine::general_purpose::STANDARD.encode(data),
+        EXPORT_FONT_FAMILY,
+    ))
+}
+
+/// Renders a memo as a standalone HTML document. Markdown is converted; plain text is kept as-is.
+fn render_html(content: &str, kind: MemoKind, name: &str, font_face: Option<&str>) -> String {
+    let (title, body) = match kind {
+        MemoKind::Text => (name.to_string(), format!("<pre>{}</pre>", escape_html(content))),
+        MemoKind::Markdown | MemoKind::Mdx => {
+            let front_matter = frontmatter::parse(content);
+            let markdown = &content[front_matter.body_offset..];
+            let options = Options::ENABLE_TASKLISTS
+                | Options::ENABLE_TABLES
+                | Options::ENABLE_STRIKETHROUGH
+                | Options::ENABLE_FOOTNOTES;
+            let mut body = String::new();
+            html::push_html(&mut body, Parser::new_ext(markdown, options));
+            let title = front_matter.title().unwrap_or(name).to_string();
+            (title, body)
+        }
+    };
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(&title),
+        STYLESHEET,
+        font_face.unwrap_or_default(),
+        body,
+    )
+}
+
+/// Reads and decodes a memo of the active working folder, returning its content, kind and name
+fn read_memo_content(state: &VaultState, crypto: &CryptoState, file_path: &str) -> Result<(String, MemoKind, String), CommandError> {
+    let folder = state.lock().active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, kind) = validate_memo_path(&folder, file_path)?;
+    let mut bytes = fs::read(&path)?;
+    if crypto::is_encrypted(&path) {
+        bytes = crypto.decrypt(&folder, &bytes)?;
+    }
+    let content = encoding::decode(&bytes)?.content;
+    let name = build_memo_meta(&path, kind).name;
+    Ok((content, kind, name))
+}
+
+/// Asks for the export target in a save dialog. `None` when cancelled.
+async fn choose_target(app: &tauri::AppHandle, filter: &str, extension: &str, name: &str) -> Result<Option<PathBuf>, CommandError> {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter(filter, &[extension])
+        .set_file_name(format!("{}.{}", name, extension))
+        .save_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+    match rx.await {
+        Ok(Some(target)) => Ok(Some(target.into_path().map_err(|e| CommandError::PathError(e.to_string()))?)),
+        Ok(None) => Ok(None),
+        Err(_) => Err(CommandError::DialogCancelled),
+    }
+}
+
+/// Exports a memo to an HTML file chosen in a save dialog. With `font_id`, that installed font
+/// is embedded, subset to the characters the memo uses. Returns the written path, or `None`
+/// when the dialog is cancelled.
+#[tauri::command]
+pub async fn export_html(
+    app: tauri::AppHandle,
+    state: State<'_, VaultState>,
+    crypto: State<'_, CryptoState>,
+    font_state: State<'_, FontState>,
+    file_path: String,
+    font_id: Option<String>,
+) -> Result<Option<String>, CommandError> {
+    let (content, kind, name) = read_memo_content(&state, &crypto, &file_path)?;
+    let font_face = match font_id {
+        Some(font_id) => Some(font_face(&font_state, &app, &font_id, &format!("{}{}", name, content))?),
+        None => None,
+    };
+    let document = render_html(&content, kind, &name, font_face.as_deref());
+
+    let Some(target) = choose_target(&app, "HTML", "html", &name).await? else {
+        return Ok(None);
+    };
+    fs::write(&target, document)?;
+    Ok(Some(target.to_string_lossy().to_string()))
+}
+
+/// Exports a memo to a PDF file chosen in a save dialog. The text is set in the installed font
+/// `font_id`, or else in the system font fontconfig matches for `font_family` (`sans-serif` by
+/// default), and the font is embedded subset to the characters used. Returns the written path,
+/// or `None` when the dialog is cancelled.
+#[tauri::command]
+pub async fn export_pdf(
+    app: tauri::AppHandle,
+    state: State<'_, VaultState>,
+    crypto: State<'_, CryptoState>,
+    font_state: State<'_, FontState>,
+    file_path: String,
+    font_id: Option<String>,
+    font_family: Option<String>,
+) -> Result<Option<String>, CommandError> {
+    let (content, kind, name) = read_memo_content(&state, &crypto, &file_path)?;
+    let (font, index) = match font_id {
+        Some(font_id) => (installed_font_data(&font_state, &app, &font_id)?, 0),
+        None => {
+            let family = font_family.as_deref().unwrap_or("sans-serif");
+            let (path, index) = fonts::match_system_font(family, &content)?;
+            (fs::read(path)?, index)
+        }
+    };
+    let document = pdf::render_pdf(&content, kind, &name, &font, index)?;
+
+    let Some(target) = choose_target(&app, "PDF", "pdf", &name).await? else {
+        return Ok(None);
+    };
+    fs::write(&target, document)?;
+    Ok(Some(target.to_string_lossy().to_string()))
+}
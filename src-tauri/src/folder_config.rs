@@ -0,0 +1,118 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::commands::{normalize_extension, CommandError};
+use crate::vault::{VaultSettings, VaultState};
+
+/// Folder inside the working folder holding its shared configuration
+pub const CONFIG_DIR: &str = ".memo";
+const CONFIG_FILE: &str = "config.toml";
+/// gitignore-syntax file in the working folder listing files that aren't memos
+pub const IGNORE_FILE: &str = ".memoignore";
+/// Emitted with the error of a broken `.memo/config.toml`, once per version of the file
+pub const FOLDER_CONFIG_ERROR_EVENT: &str = "folder-config-error";
+
+/// `.memo/config.toml` in the working folder. Everything is optional; what is set overrides
+/// the personal settings of whoever opens the folder, so a shared vault behaves the same for all.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct FolderConfig {
+    /// Globs of files to leave out of listings, matched against paths relative to the folder
+    pub ignore: Vec<String>,
+    /// Overrides `VaultSettings::default_extension`
+    pub default_extension: Option<String>,
+}
+
+impl FolderConfig {
+    /// Applies the overrides to a vault's personal settings
+    pub fn apply(&self, settings: &mut VaultSettings) {
+        if let Some(ext) = &self.default_extension {
+            settings.default_extension = ext.clone();
+        }
+    }
+
+    pub fn ignore_set(&self) -> Result<GlobSet, CommandError> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &self.ignore {
+            let glob = Glob::new(pattern).map_err(|e| invalid(format!("ignore: {}", e)))?;
+            builder.add(glob);
+        }
+        builder.build().map_err(|e| invalid(format!("ignore: {}", e)))
+    }
+}
+
+fn invalid(reason: String) -> CommandError {
+    CommandError::InvalidFolderConfig(format!("{}/{}: {}", CONFIG_DIR, CONFIG_FILE, reason))
+}
+
+/// Reads and validates the folder's configuration; a folder without one gets the defaults
+pub fn load(folder: &Path) -> Result<FolderConfig, CommandError> {
+    let file = folder.join(CONFIG_DIR).join(CONFIG_FILE);
+    if !file.is_file() {
+        return Ok(FolderConfig::default());
+    }
+    let config: FolderConfig = toml::from_str(&fs::read_to_string(&file)?).map_err(|e| invalid(e.message().to_string()))?;
+
+    // Checks the globs up front so a typo is reported here rather than on every listing
+    config.ignore_set()?;
+
+    Ok(FolderConfig {
+        default_extension: config.default_extension.as_deref().map(normalize_extension).transpose()?,
+        ..config
+    })
+}
+
+/// A loaded config file, reused until its size or mtime changes
+struct CachedConfig {
+    stamp: Option<(u64, SystemTime)>,
+    config: Result<FolderConfig, String>,
+    reported: bool,
+}
+
+/// Configs by file. Kept here rather than in managed state because `scan_memos` and
+/// `active_settings`, which every listing and the reminder scheduler go through, have no app handle.
+static CACHE: LazyLock<Mutex<HashMap<PathBuf, CachedConfig>>> = LazyLock::new(Default::default);
+
+fn lock_cache() -> MutexGuard<'static, HashMap<PathBuf, CachedConfig>> {
+    CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The configuration the commands use. A broken file in a shared vault must not stop anyone
+/// from listing or creating memos, so it is ignored; `report_errors` tells the user once, and
+/// `get_folder_config` reports it too.
+pub fn load_or_default(folder: &Path) -> FolderConfig {
+    let file = folder.join(CONFIG_DIR).join(CONFIG_FILE);
+    let stamp = fs::metadata(&file)
+        .ok()
+        .and_then(|metadata| Some((metadata.len(), metadata.modified().ok()?)));
+    let mut cache = lock_cache();
+    let fresh = cache.get(&file).is_some_and(|cached| cached.stamp.is_some() && cached.stamp == stamp);
+    if !fresh {
+        let config = load(folder).map_err(|error| error.to_string());
+        cache.insert(file.clone(), CachedConfig { stamp, config, reported: false });
+    }
+    cache[&file].config.clone().unwrap_or_default()
+}
+
+/// Emits `FOLDER_CONFIG_ERROR_EVENT` for config files found broken since the last call
+pub fn report_errors(app: &AppHandle) {
+    let mut cache = lock_cache();
+    for cached in cache.values_mut().filter(|cached| !cached.reported) {
+        cached.reported = true;
+        if let Err(error) = &cached.config {
+            let _ = app.emit(FOLDER_CONFIG_ERROR_EVENT, error);
+        }
+    }
+}
+
+/// The working folder's `.memo/config.toml`, validated
+#[tauri::command]
+pub fn get_folder_config(state: State<'_, VaultState>) -> Result<FolderConfig, CommandError> {
+    load(&state.lock().active_folder()?)
+}
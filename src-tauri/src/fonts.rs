@@ -0,0 +1,885 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard};
+use std::time::UNIX_EPOCH;
+use tauri::http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, CACHE_CONTROL, CONTENT_TYPE};
+use tauri::http::{Request, Response, StatusCode};
+use ttf_parser::name::Table as NameTable;
+use ttf_parser::{cmap, name_id, os2, Face, GlyphId, Language, PlatformId};
+
+use crate::commands::{is_safe_filename, CommandError, InstalledFont};
+use crate::storage;
+
+/// Formats a font can be installed as; also the file extensions in the fonts directory
+pub const FONT_FORMATS: [&str; 4] = ["ttf", "otf", "woff", "woff2"];
+/// Directory of the installed fonts, inside the app data directory
+const FONTS_DIR: &str = "fonts";
+/// Manifest of the installed fonts, kept next to the font files
+const MANIFEST_FILE: &str = "fonts.json";
+/// URI scheme installed fonts are served from: `memofont://localhost/<font id>`
+pub const FONT_PROTOCOL: &str = "memofont";
+
+/// Upper bound for a decompressed WOFF/WOFF2 font, against decompression bombs
+const MAX_DECOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+/// Magic number every `head` table carries
+const HEAD_MAGIC: u32 = 0x5F0F_3CF5;
+
+/// Tables read from a font; everything else is left alone
+const NAME: [u8; 4] = *b"name";
+const CMAP: [u8; 4] = *b"cmap";
+const OS2: [u8; 4] = *b"OS/2";
+const HEAD: [u8; 4] = *b"head";
+
+/// Table tags WOFF2 encodes as a 6-bit index instead of spelling them out
+const WOFF2_KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm", b"glyf", b"loca",
+    b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern", b"LTSH", b"PCLT", b"VDMX", b"vhea",
+    b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC", b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL",
+    b"SVG ", b"sbix", b"acnt", b"avar", b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar",
+    b"gvar", b"hsty", b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+/// A script counts as supported when the font has glyphs for all of its sample characters
+const SCRIPT_SAMPLES: [(&str, &str); 10] = [
+    ("latin", "AZaz"),
+    ("greek", "ΑΩαω"),
+    ("cyrillic", "АЯая"),
+    ("arabic", "ابتي"),
+    ("hebrew", "אבגת"),
+    ("thai", "กขคฮ"),
+    ("han", "中文字国"),
+    // Kana plus kanji common in Japanese text
+    ("japanese", "あいんアイン日本語漢字"),
+    ("korean", "가나한글"),
+    ("devanagari", "अकखह"),
+];
+
+/// fontconfig language codes standing for each script in `SCRIPT_SAMPLES`
+const SCRIPT_LANGS: [(&str, &[&str]); 10] = [
+    ("latin", &["en"]),
+    ("greek", &["el"]),
+    ("cyrillic", &["ru"]),
+    ("arabic", &["ar"]),
+    ("hebrew", &["he"]),
+    ("thai", &["th"]),
+    ("han", &["zh-cn", "zh-tw", "zh-hk"]),
+    ("japanese", &["ja"]),
+    ("korean", &["ko"]),
+    ("devanagari", &["hi"]),
+];
+
+/// What `install_font` learns from the font file itself
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FontInfo {
+    /// `ttf`, `otf`, `woff` or `woff2`, by content rather than file extension
+    pub format: String,
+    pub family: String,
+    /// Subfamily name, e.g. "Regular" or "Bold Italic"
+    pub style: String,
+    /// 100–900, from the `OS/2` table
+    pub weight: u16,
+    pub italic: bool,
+    /// Scripts the font has glyphs for, e.g. `["latin", "han", "japanese"]`
+    pub scripts: Vec<String>,
+}
+
+impl FontInfo {
+    /// "Family" for regular faces, "Family Style" otherwise
+    pub fn display_name(&self) -> String {
+        if self.style.is_empty() || self.style.eq_ignore_ascii_case("regular") {
+            self.family.clone()
+        } else {
+            format!("{} {}", self.family, self.style)
+        }
+    }
+}
+
+fn invalid(reason: &str) -> CommandError {
+    CommandError::UnsupportedFontFormat(reason.to_string())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn decompress(reader: impl Read, expected: usize) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(expected.min(MAX_DECOMPRESSED_SIZE as usize));
+    reader.take(MAX_DECOMPRESSED_SIZE).read_to_end(&mut output).ok()?;
+    (output.len() == expected).then_some(output)
+}
+
+/// Tables read to describe a font
+const INFO_TABLES: [[u8; 4]; 4] = [NAME, CMAP, OS2, HEAD];
+
+/// Tables a font is read into: by tag, in tag order
+type Tables = BTreeMap<[u8; 4], Vec<u8>>;
+
+/// Whether a table is extracted; `None` wants every table
+fn is_wanted(wanted: Option<&[[u8; 4]]>, tag: &[u8; 4]) -> bool {
+    wanted.is_none_or(|wanted| wanted.contains(tag))
+}
+
+/// Plain TrueType/OpenType, or the font at `index` of a collection. `Face::parse` also checks
+/// the mandatory tables are present.
+fn sfnt_tables(data: &[u8], index: u32, wanted: Option<&[[u8; 4]]>) -> Option<Tables> {
+    let face = Face::parse(data, index).ok()?;
+    Some(
+        face.raw_face()
+            .table_records
+            .into_iter()
+            .filter(|record| is_wanted(wanted, &record.tag.to_bytes()))
+            .filter_map(|record| {
+                let start = record.offset as usize;
+                let table = data.get(start..start.checked_add(record.length as usize)?)?;
+                Some((record.tag.to_bytes(), table.to_vec()))
+            })
+            .collect(),
+    )
+}
+
+/// WOFF 1.0: a table directory followed by individually zlib-compressed tables
+fn woff_tables(data: &[u8], wanted: Option<&[[u8; 4]]>) -> Option<Tables> {
+    let num_tables = usize::from(read_u16(data, 12)?);
+    let mut tables = Tables::new();
+    for i in 0..num_tables {
+        let entry = 44 + i * 20;
+        let tag: [u8; 4] = data.get(entry..entry + 4)?.try_into().ok()?;
+        if !is_wanted(wanted, &tag) {
+            continue;
+        }
+        let offset = read_u32(data, entry + 4)? as usize;
+        let comp_length = read_u32(data, entry + 8)? as usize;
+        let orig_length = read_u32(data, entry + 12)? as usize;
+        let stored = data.get(offset..offset.checked_add(comp_length)?)?;
+        let table = match comp_length.cmp(&orig_length) {
+            std::cmp::Ordering::Equal => stored.to_vec(),
+            std::cmp::Ordering::Less => decompress(flate2::read::ZlibDecoder::new(stored), orig_length)?,
+            std::cmp::Ordering::Greater => return None,
+        };
+        tables.insert(tag, table);
+    }
+    Some(tables)
+}
+
+/// Reads a WOFF2 UIntBase128, returning the value and the bytes consumed
+fn read_base128(data: &[u8], offset: usize) -> Option<(u32, usize)> {
+    let mut value: u32 = 0;
+    for i in 0..5 {
+        let byte = *data.get(offset + i)?;
+        // Leading zeros are invalid
+        if i == 0 && byte == 0x80 {
+            return None;
+        }
+        value = value.checked_mul(128)? | u32::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// WOFF 2.0: a compact table directory and one brotli stream holding all tables back to back.
+/// Only `glyf`, `loca` and `hmtx` are ever transformed; undoing that isn't supported, so
+/// wanting a transformed table fails.
+fn woff2_tables(data: &[u8], wanted: Option<&[[u8; 4]]>) -> Option<Tables> {
+    // Font collections are not supported
+    if data.get(4..8)? == b"ttcf" {
+        return None;
+    }
+    let num_tables = usize::from(read_u16(data, 12)?);
+    let total_compressed_size = read_u32(data, 20)? as usize;
+
+    let mut offset = 48;
+    let mut directory = Vec::with_capacity(num_tables);
+    for _ in 0..num_tables {
+        let flags = *data.get(offset)?;
+        offset += 1;
+        let tag: [u8; 4] = match usize::from(flags & 0x3F) {
+            63 => {
+                let tag = data.get(offset..offset + 4)?.try_into().ok()?;
+                offset += 4;
+                tag
+            }
+            index => *WOFF2_KNOWN_TAGS[index],
+        };
+        let (orig_length, consumed) = read_base128(data, offset)?;
+        offset += consumed;
+        let transform_version = flags >> 6;
+        // For glyf and loca version 0 means transformed; for every other table it means as-is
+        let transformed = if &tag == b"glyf" || &tag == b"loca" {
+            transform_version != 3
+        } else {
+            transform_version != 0
+        };
+        let length = if transformed {
+            let (transform_length, consumed) = read_base128(data, offset)?;
+            offset += consumed;
+            transform_length
+        } else {
+            orig_length
+        };
+        directory.push((tag, length as usize, transformed));
+    }
+
+    let compressed = data.get(offset..offset.checked_add(total_compressed_size)?)?;
+    let stream_length: usize = directory.iter().map(|(_, length, _)| length).sum();
+    let stream = decompress(brotli_decompressor::Decompressor::new(compressed, 4096), stream_length)?;
+
+    let mut tables = Tables::new();
+    let mut position = 0;
+    for (tag, length, transformed) in directory {
+        if is_wanted(wanted, &tag) {
+            if transformed {
+                return None;
+            }
+            tables.insert(tag, stream.get(position..position + length)?.to_vec());
+        }
+        position += length;
+    }
+    Some(tables)
+}
+
+/// Picks a name record, preferring US English, then any other Unicode or ASCII record
+fn find_name(names: &NameTable<'_>, ids: &[u16]) -> Option<String> {
+    let decode = |record: &ttf_parser::name::Name<'_>| {
+        record.to_string().or_else(|| {
+            (record.platform_id == PlatformId::Macintosh && record.name.is_ascii())
+                .then(|| String::from_utf8_lossy(record.name).to_string())
+        })
+    };
+    ids.iter().find_map(|id| {
+        let records: Vec<_> = names.names.into_iter().filter(|record| record.name_id == *id).collect();
+        records
+            .iter()
+            .filter(|record| record.language() == Language::English_UnitedStates)
+            .chain(records.iter())
+            .filter_map(decode)
+            .map(|name| name.trim().to_string())
+            .find(|name| !name.is_empty())
+    })
+}
+
+fn has_glyph(cmap: &cmap::Table<'_>, c: char) -> bool {
+    cmap.subtables
+        .into_iter()
+        .filter(|subtable| subtable.is_unicode())
+        .any(|subtable| subtable.glyph_index(u32::from(c)).is_some_and(|glyph| glyph.0 != 0))
+}
+
+/// Identifies the font format and reads the given tables (all of them for `None`)
+fn read_tables(data: &[u8], wanted: Option<&[[u8; 4]]>) -> Result<(&'static str, Tables), CommandError> {
+    let signature = data.get(0..4).ok_or_else(|| invalid("file is too short to be a font"))?;
+    let (format, tables) = match signature {
+        [0x00, 0x01, 0x00, 0x00] | b"true" => ("ttf", sfnt_tables(data, 0, wanted)),
+        b"OTTO" => ("otf", sfnt_tables(data, 0, wanted)),
+        b"wOFF" => ("woff", woff_tables(data, wanted)),
+        b"wOF2" => ("woff2", woff2_tables(data, wanted)),
+        b"ttcf" => return Err(invalid("font collections (.ttc) are not supported")),
+        _ => return Err(invalid("not a font file")),
+    };
+    let tables = tables.ok_or_else(|| invalid(&format!("corrupt {} file", format)))?;
+    Ok((format, tables))
+}
+
+/// Parses a font file, identifying its format by content. Anything that isn't a readable
+/// TrueType, OpenType, WOFF or WOFF2 font is rejected with `UnsupportedFontFormat`.
+pub fn inspect_font(data: &[u8]) -> Result<FontInfo, CommandError> {
+    let (format, tables) = read_tables(data, Some(&INFO_TABLES))?;
+
+    let head = tables.get(&HEAD).ok_or_else(|| invalid("missing head table"))?;
+    if read_u32(head, 12) != Some(HEAD_MAGIC) {
+        return Err(invalid("corrupt head table"));
+    }
+    let names = tables
+        .get(&NAME)
+        .and_then(|table| NameTable::parse(table))
+        .ok_or_else(|| invalid("missing or corrupt name table"))?;
+    let cmap = tables
+        .get(&CMAP)
+        .and_then(|table| cmap::Table::parse(table))
+        .ok_or_else(|| invalid("missing or corrupt cmap table"))?;
+    let os2 = tables.get(&OS2).and_then(|table| os2::Table::parse(table));
+
+    let family = find_name(&names, &[name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY])
+        .ok_or_else(|| invalid("font has no family name"))?;
+    let style = find_name(&names, &[name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY]).unwrap_or_default();
+    let italic = match os2 {
+        Some(os2) => os2.style() != os2::Style::Normal,
+        None => style.to_lowercase().contains("italic") || style.to_lowercase().contains("oblique"),
+    };
+    let scripts = SCRIPT_SAMPLES
+        .iter()
+        .filter(|(_, sample)| sample.chars().all(|c| has_glyph(&cmap, c)))
+        .map(|(script, _)| script.to_string())
+        .collect();
+
+    Ok(FontInfo {
+        format: format.to_string(),
+        family,
+        style,
+        weight: os2.map_or(400, |os2| os2.weight().to_number()),
+        italic,
+        scripts,
+    })
+}
+
+fn checksum(table: &[u8]) -> u32 {
+    table
+        .chunks(4)
+        .map(|chunk| {
+            let mut word = [0u8; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_be_bytes(word)
+        })
+        .fold(0, u32::wrapping_add)
+}
+
+/// A table's directory checksum; `head` is summed with checkSumAdjustment taken as 0
+fn table_checksum(tag: &[u8; 4], table: &[u8]) -> u32 {
+    match read_u32(table, 8) {
+        Some(adjustment) if *tag == HEAD => checksum(table).wrapping_sub(adjustment),
+        _ => checksum(table),
+    }
+}
+
+fn pad4(data: &mut Vec<u8>) {
+    data.resize(data.len().next_multiple_of(4), 0);
+}
+
+/// Assembles an sfnt (TrueType/OpenType) font from its tables
+fn sfnt_file(flavor: [u8; 4], tables: &Tables) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let entry_selector = num_tables.max(1).ilog2() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut file = Vec::new();
+    file.extend(flavor);
+    for field in [num_tables, search_range, entry_selector, num_tables * 16 - search_range] {
+        file.extend(field.to_be_bytes());
+    }
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, table) in tables {
+        file.extend(tag);
+        file.extend(table_checksum(tag, table).to_be_bytes());
+        file.extend((offset as u32).to_be_bytes());
+        file.extend((table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+    let mut head_offset = None;
+    for (tag, table) in tables {
+        if *tag == HEAD {
+            head_offset = Some(file.len());
+        }
+        file.extend(table);
+        pad4(&mut file);
+    }
+    // head.checkSumAdjustment makes the whole file sum to 0xB1B0AFBA
+    if let Some(head) = head_offset.filter(|head| file.len() >= head + 12) {
+        file[head + 8..head + 12].fill(0);
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&file));
+        file[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    file
+}
+
+/// Packs an sfnt font as WOFF 1.0, zlib-compressing each table where that helps
+fn woff_file(sfnt: &[u8]) -> Option<Vec<u8>> {
+    let flavor: [u8; 4] = sfnt.get(0..4)?.try_into().ok()?;
+    let tables = sfnt_tables(sfnt, 0, None)?;
+    let header_length = 44 + tables.len() * 20;
+
+    let mut directory = Vec::new();
+    let mut body = Vec::new();
+    for (tag, table) in &tables {
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(table).ok()?;
+        let compressed = encoder.finish().ok()?;
+        let stored = if compressed.len() < table.len() { &compressed } else { table };
+        directory.extend(tag);
+        directory.extend(((header_length + body.len()) as u32).to_be_bytes());
+        directory.extend((stored.len() as u32).to_be_bytes());
+        directory.extend((table.len() as u32).to_be_bytes());
+        directory.extend(table_checksum(tag, table).to_be_bytes());
+        body.extend(stored);
+        pad4(&mut body);
+    }
+
+    let mut file = Vec::with_capacity(header_length + body.len());
+    file.extend(b"wOFF");
+    file.extend(flavor);
+    file.extend(((header_length + body.len()) as u32).to_be_bytes());
+    file.extend((tables.len() as u16).to_be_bytes());
+    file.extend(0u16.to_be_bytes());
+    file.extend((sfnt.len() as u32).to_be_bytes());
+    // Version 1.0, then no metadata or private block
+    file.extend(1u16.to_be_bytes());
+    file.extend(0u16.to_be_bytes());
+    file.extend([0u8; 20]);
+    file.extend(directory);
+    file.extend(body);
+    Some(file)
+}
+
+/// Builds a `cmap` table: format 4 for the BMP (when it fits) and format 12 for everything
+fn cmap_table(mapping: &BTreeMap<u32, u16>) -> Vec<u8> {
+    let mut format12 = Vec::new();
+    format12.extend(12u16.to_be_bytes());
+    format12.extend(0u16.to_be_bytes());
+    format12.extend(((16 + mapping.len() * 12) as u32).to_be_bytes());
+    format12.extend(0u32.to_be_bytes());
+    format12.extend((mapping.len() as u32).to_be_bytes());
+    for (&c, &glyph) in mapping {
+        format12.extend(c.to_be_bytes());
+        format12.extend(c.to_be_bytes());
+        format12.extend(u32::from(glyph).to_be_bytes());
+    }
+
+    // One segment per character plus the closing 0xFFFF segment
+    let bmp: Vec<(u16, u16)> = mapping
+        .iter()
+        .filter_map(|(&c, &glyph)| Some((u16::try_from(c).ok().filter(|&c| c != 0xFFFF)?, glyph)))
+        .collect();
+    let seg_count = bmp.len() + 1;
+    let format4_length = 16 + seg_count * 8;
+    let format4 = (format4_length <= usize::from(u16::MAX)).then(|| {
+        let entry_selector = (seg_count as u16).ilog2() as u16;
+        let search_range = (1u16 << entry_selector) * 2;
+        let mut table = Vec::new();
+        for field in [4, format4_length as u16, 0, seg_count as u16 * 2, search_range, entry_selector] {
+            table.extend(u16::to_be_bytes(field));
+        }
+        table.extend((seg_count as u16 * 2 - search_range).to_be_bytes());
+        bmp.iter().for_each(|(c, _)| table.extend(c.to_be_bytes()));
+        table.extend(0xFFFFu16.to_be_bytes());
+        table.extend(0u16.to_be_bytes());
+        bmp.iter().for_each(|(c, _)| table.extend(c.to_be_bytes()));
+        table.extend(0xFFFFu16.to_be_bytes());
+        bmp.iter().for_each(|(c, glyph)| table.extend(glyph.wrapping_sub(*c).to_be_bytes()));
+        table.extend(1u16.to_be_bytes());
+        table.extend(vec![0u8; seg_count * 2]);
+        table
+    });
+
+    let subtables: Vec<(u16, Vec<u8>)> = format4.map(|table| (1, table)).into_iter().chain([(10, format12)]).collect();
+    let mut table = Vec::new();
+    table.extend(0u16.to_be_bytes());
+    table.extend((subtables.len() as u16).to_be_bytes());
+    let mut offset = 4 + subtables.len() * 8;
+    for (encoding, subtable) in &subtables {
+        table.extend(3u16.to_be_bytes());
+        table.extend(encoding.to_be_bytes());
+        table.extend((offset as u32).to_be_bytes());
+        offset += subtable.len();
+    }
+    subtables.iter().for_each(|(_, subtable)| table.extend(subtable));
+    table
+}
+
+/// Cuts a font down to the glyphs needed for `text` and returns it as TrueType or CFF-flavored
+/// OpenType. The subset keeps outlines, metrics and names, and gets a cmap for the new glyph
+/// IDs; layout tables (kerning, ligatures) are dropped with the old glyph IDs. `index` picks
+/// the font of a collection (`.ttc`).
+pub fn subset_sfnt(data: &[u8], index: u32, text: &str) -> Result<Vec<u8>, CommandError> {
+    let tables = match data.get(0..4) {
+        Some(b"ttcf") => sfnt_tables(data, index, None).ok_or_else(|| invalid("corrupt font collection"))?,
+        _ => {
+            read_tables(data, None)
+                .map_err(|e| match data.get(0..4) {
+                    Some(b"wOF2") => invalid("WOFF2 fonts with transformed outlines can't be subset"),
+                    _ => e,
+                })?
+                .1
+        }
+    };
+    let flavor: [u8; 4] = if tables.contains_key(b"CFF ") { *b"OTTO" } else { [0, 1, 0, 0] };
+    let sfnt = sfnt_file(flavor, &tables);
+    let face = Face::parse(&sfnt, 0).map_err(|_| invalid("corrupt font file"))?;
+
+    let chars: BTreeMap<u32, GlyphId> = text
+        .chars()
+        .filter_map(|c| Some((u32::from(c), face.glyph_index(c)?)))
+        .collect();
+    let mut remapper = subsetter::GlyphRemapper::new();
+    let mapping: BTreeMap<u32, u16> = chars.into_iter().map(|(c, glyph)| (c, remapper.remap(glyph.0))).collect();
+    let subset = subsetter::subset(&sfnt, 0, &remapper).map_err(|e| invalid(&format!("could not subset font: {}", e)))?;
+
+    // The subsetter targets PDF and drops cmap and OS/2, which browsers need
+    let mut subset_tables = sfnt_tables(&subset, 0, None).ok_or_else(|| invalid("could not subset font"))?;
+    subset_tables.insert(CMAP, cmap_table(&mapping));
+    if let Some(os2) = tables.get(&OS2) {
+        subset_tables.insert(OS2, os2.clone());
+    }
+    let subset_flavor: [u8; 4] = subset.get(0..4).and_then(|flavor| flavor.try_into().ok()).unwrap_or(flavor);
+    Ok(sfnt_file(subset_flavor, &subset_tables))
+}
+
+/// `subset_sfnt` packed as WOFF, for embedding in web pages
+pub fn subset_font(data: &[u8], text: &str) -> Result<Vec<u8>, CommandError> {
+    woff_file(&subset_sfnt(data, 0, text)?).ok_or_else(|| invalid("could not subset font"))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FontManifest {
+    fonts: Vec<InstalledFont>,
+}
+
+/// Managed state serializing access to `fonts.json`
+#[derive(Default)]
+pub struct FontState(Mutex<()>);
+
+impl FontState {
+    pub fn lock(&self) -> MutexGuard<'_, ()> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// `<app data>/fonts`, created on first use
+pub fn fonts_dir(app: &tauri::AppHandle) -> Result<PathBuf, CommandError> {
+    let dir = storage::app_data_dir(app)?.join(FONTS_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Hex SHA-256 of a font file, used to spot the same font being installed twice
+pub fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Manifest entry for a font file already in the fonts directory
+pub fn font_entry(id: String, label: Option<String>, data: &[u8], installed_at: u64) -> Result<InstalledFont, CommandError> {
+    let info = inspect_font(data)?;
+    let label = label
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+        .unwrap_or_else(|| info.display_name());
+    Ok(InstalledFont {
+        filename: format!("{}.{}", id, info.format),
+        id,
+        label,
+        format: info.format,
+        installed_at,
+        sha256: content_hash(data),
+        family: info.family,
+        style: info.style,
+        weight: info.weight,
+        italic: info.italic,
+        scripts: info.scripts,
+    })
+}
+
+/// Entries must name a file directly inside the fonts directory
+fn is_valid_entry(dir: &Path, font: &InstalledFont) -> bool {
+    is_safe_filename(&font.id)
+        && FONT_FORMATS.contains(&font.format.as_str())
+        && font.filename == format!("{}.{}", font.id, font.format)
+        && dir.join(&font.filename).is_file()
+}
+
+/// Reads the manifest and brings it in line with the directory: entries whose file is gone are
+/// dropped, and font files nobody tracks (e.g. installed before the manifest existed) are adopted.
+/// The manifest is rewritten only when something changed.
+pub fn load_fonts(dir: &Path) -> Result<Vec<InstalledFont>, CommandError> {
+    let mut manifest: FontManifest = storage::read_json(&dir.join(MANIFEST_FILE))?;
+    let count = manifest.fonts.len();
+    manifest.fonts.retain(|font| is_valid_entry(dir, font));
+    let mut changed = manifest.fonts.len() != count;
+
+    let tracked: HashSet<String> = manifest.fonts.iter().map(|font| font.filename.clone()).collect();
+    let mut orphans: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|ext| FONT_FORMATS.iter().any(|format| ext.eq_ignore_ascii_case(format)))
+                && path.file_name().is_some_and(|name| !tracked.contains(&*name.to_string_lossy()))
+        })
+        .collect();
+    orphans.sort();
+    for path in orphans {
+        let Some(id) = path.file_stem().map(|stem| stem.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let installed_at = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_millis() as u64);
+        // Files that don't parse, or whose extension doesn't match their content, are left alone
+        let Ok(data) = fs::read(&path) else { continue };
+        match font_entry(id, None, &data, installed_at) {
+            Ok(font) if dir.join(&font.filename) == path => {
+                manifest.fonts.push(font);
+                changed = true;
+            }
+            _ => {}
+        }
+    }
+
+    if changed {
+        storage::write_json(&dir.join(MANIFEST_FILE), &manifest)?;
+    }
+    Ok(manifest.fonts)
+}
+
+/// Looks a font up in the manifest. Paths are only ever built from registered entries.
+pub fn registered_font(dir: &Path, font_id: &str) -> Result<InstalledFont, CommandError> {
+    load_fonts(dir)?
+        .into_iter()
+        .find(|font| font.id == font_id)
+        .ok_or_else(|| CommandError::FileNotFound(font_id.to_string()))
+}
+
+/// Looks a font up in the manifest as it is on disk, skipping the repairs of `load_fonts`, so
+/// serving a font never writes anything
+fn manifest_font(dir: &Path, font_id: &str) -> Result<InstalledFont, CommandError> {
+    let manifest: FontManifest = storage::read_json(&dir.join(MANIFEST_FILE))?;
+    manifest
+        .fonts
+        .into_iter()
+        .find(|font| font.id == font_id && is_valid_entry(dir, font))
+        .ok_or_else(|| CommandError::FileNotFound(font_id.to_string()))
+}
+
+pub fn save_fonts(dir: &Path, fonts: Vec<InstalledFont>) -> Result<(), CommandError> {
+    storage::write_json(&dir.join(MANIFEST_FILE), &FontManifest { fonts })
+}
+
+fn font_mime(format: &str) -> &'static str {
+    match format {
+        "otf" => "font/otf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        _ => "font/ttf",
+    }
+}
+
+/// Handler for `FONT_PROTOCOL`, run off the main thread. Font files never change once installed
+/// (a new file gets a new ID), so responses may be cached indefinitely. Read-only: manifest
+/// writes are atomic, so no lock is needed against a concurrent install or removal.
+pub fn serve_font(app: &tauri::AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let font_id = request.uri().path().trim_start_matches('/');
+    let found = (|| {
+        let dir = storage::app_data_dir(app)?.join(FONTS_DIR);
+        let font = manifest_font(&dir, font_id)?;
+        let data = fs::read(dir.join(&font.filename))?;
+        Ok::<_, CommandError>((font, data))
+    })();
+
+    let response = match found {
+        Ok((font, data)) => Response::builder()
+            .header(CONTENT_TYPE, font_mime(&font.format))
+            .header(CACHE_CONTROL, "public, max-age=31536000, immutable")
+            // The webview's own origin differs from the scheme's
+            .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .body(data),
+        Err(e) => Response::builder()
+            .status(match e {
+                CommandError::FileNotFound(_) => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            })
+            .header(CONTENT_TYPE, "text/plain")
+            .body(e.to_string().into_bytes()),
+    };
+    response.unwrap_or_default()
+}
+
+/// A font installed on the system, usable by family name without copying it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SystemFont {
+    pub family: String,
+    pub style: String,
+    /// 100–900, converted from the fontconfig weight
+    pub weight: u16,
+    pub italic: bool,
+    /// Same script names as `InstalledFont::scripts`
+    pub scripts: Vec<String>,
+    pub path: String,
+}
+
+/// fontconfig weights (0–210) to CSS weights
+fn css_weight(weight: &str) -> u16 {
+    // Variable fonts report a range such as `[0 210]`
+    let Ok(weight) = weight.parse::<f32>() else {
+        return 400;
+    };
+    match weight {
+        w if w <= 0.0 => 100,
+        w if w <= 40.0 => 200,
+        w if w <= 55.0 => 300,
+        w if w <= 80.0 => 400,
+        w if w <= 100.0 => 500,
+        w if w <= 180.0 => 600,
+        w if w <= 200.0 => 700,
+        w if w <= 205.0 => 800,
+        _ => 900,
+    }
+}
+
+/// Parses one line of the `fc-list` output requested in `list_system_fonts`
+fn parse_fc_line(line: &str) -> Option<SystemFont> {
+    let mut fields = line.split('\t');
+    // Multi-valued fields list the preferred name first
+    let family = fields.next()?.split(',').next()?.trim().to_string();
+    let style = fields.next()?.split(',').next()?.trim().to_string();
+    let weight = css_weight(fields.next()?.trim());
+    let italic = fields.next()?.trim().parse::<u32>().is_ok_and(|slant| slant > 0);
+    let langs: HashSet<&str> = fields.next()?.split('|').collect();
+    let path = fields.next()?.trim().to_string();
+    if family.is_empty() || path.is_empty() {
+        return None;
+    }
+    let scripts = SCRIPT_LANGS
+        .iter()
+        .filter(|(_, codes)| codes.iter().any(|code| langs.contains(code)))
+        .map(|(script, _)| script.to_string())
+        .collect();
+    Some(SystemFont { family, style, weight, italic, scripts, path })
+}
+
+fn system_fonts() -> Result<Vec<SystemFont>, CommandError> {
+    let output = match Command::new("fc-list")
+        .args([":scalable=true", "--format", "%{family}\t%{style}\t%{weight}\t%{slant}\t%{lang}\t%{file}\n"])
+        .output()
+    {
+        Ok(output) => output,
+        // No fontconfig (e.g. Windows or macOS): there is nothing to list
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(CommandError::SystemFontError(e.to_string())),
+    };
+    if !output.status.success() {
+        return Err(CommandError::SystemFontError(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    let mut fonts: Vec<SystemFont> = String::from_utf8_lossy(&output.stdout).lines().filter_map(parse_fc_line).collect();
+    fonts.sort_by(|a, b| {
+        a.family
+            .to_lowercase()
+            .cmp(&b.family.to_lowercase())
+            .then(a.italic.cmp(&b.italic))
+            .then(a.weight.cmp(&b.weight))
+            .then(a.style.cmp(&b.style))
+    });
+    // The same face can be installed in more than one place
+    fonts.dedup_by(|a, b| a.family == b.family && a.style == b.style);
+    Ok(fonts)
+}
+
+/// The system font fontconfig picks for a family (or a generic name like `sans-serif`), as its
+/// file and index within a collection. Text with Japanese in it asks for a face covering it.
+pub fn match_system_font(family: &str, text: &str) -> Result<(PathBuf, u32), CommandError> {
+    let japanese = text.chars().any(|c| matches!(c, '\u{3040}'..='\u{30FF}' | '\u{4E00}'..='\u{9FFF}'));
+    let pattern = if japanese { format!("{}:lang=ja", family) } else { family.to_string() };
+    let output = Command::new("fc-match")
+        .args([pattern.as_str(), "--format", "%{file}\t%{index}"])
+        .output()
+        .map_err(|e| CommandError::SystemFontError(format!("fc-match: {}", e)))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (file, index) = stdout.split_once('\t').unwrap_or((stdout.as_ref(), "0"));
+    if !output.status.success() || file.trim().is_empty() {
+        return Err(CommandError::SystemFontError(format!("no system font matches '{}'", family)));
+    }
+    Ok((PathBuf::from(file.trim()), index.trim().parse().unwrap_or(0)))
+}
+
+/// Lists the fonts installed on the system through fontconfig, sorted by family.
+/// Returns an empty list where fontconfig isn't available.
+#[tauri::command]
+pub async fn list_system_fonts() -> Result<Vec<SystemFont>, CommandError> {
+    tauri::async_runtime::spawn_blocking(system_fonts)
+        .await
+        .map_err(|e| CommandError::SystemFontError(e.to_string()))?
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ttf_parser::cmap::Format;
+
+    /// `head`, `hhea` and `maxp`, the least `Face::parse` accepts, for a font of `glyphs` empty glyphs
+    fn minimal_tables(glyphs: u16) -> Tables {
+        let mut head = vec![0u8; 54];
+        head[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        head[12..16].copy_from_slice(&HEAD_MAGIC.to_be_bytes());
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut hhea = vec![0u8; 36];
+        hhea[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        let mut maxp = 0x0000_5000u32.to_be_bytes().to_vec();
+        maxp.extend(glyphs.to_be_bytes());
+        Tables::from([(HEAD, head), (*b"hhea", hhea), (*b"maxp", maxp)])
+    }
+
+    /// A TrueType font with a square glyph for each character of `chars`, all 500 units wide
+    /// (spaces included, as empty glyphs), for tests that need real outlines
+    pub(crate) fn truetype_fixture(chars: &str) -> Vec<u8> {
+        let mut mapping = BTreeMap::new();
+        for c in chars.chars() {
+            let next = mapping.len() as u16 + 1;
+            mapping.entry(u32::from(c)).or_insert(next);
+        }
+        let glyphs = mapping.len() as u16 + 1;
+        let mut tables = minimal_tables(glyphs);
+        tables.get_mut(b"hhea").unwrap()[34..36].copy_from_slice(&glyphs.to_be_bytes());
+        // Header, one contour, four on-curve flags, then x and y deltas of (50, 0)–(450, 700)
+        let square: Vec<u8> = [1i16, 50, 0, 450, 700, 3, 0]
+            .into_iter()
+            .flat_map(i16::to_be_bytes)
+            .chain([1u8; 4])
+            .chain([50i16, 400, 0, -400, 0, 0, 700, 0].into_iter().flat_map(i16::to_be_bytes))
+            .collect();
+        let (mut glyf, mut loca, mut hmtx) = (Vec::new(), vec![0u8; 2], Vec::new());
+        hmtx.extend([500u16, 0].into_iter().flat_map(u16::to_be_bytes));
+        let mut outlines: Vec<(u16, char)> = mapping.iter().map(|(&c, &glyph)| (glyph, char::from_u32(c).unwrap())).collect();
+        outlines.sort();
+        for (_, c) in outlines {
+            if !c.is_whitespace() {
+                glyf.extend(&square);
+            }
+            loca.extend((glyf.len() as u16 / 2).to_be_bytes());
+            hmtx.extend([500u16, 50].into_iter().flat_map(u16::to_be_bytes));
+        }
+        tables.insert(*b"glyf", glyf);
+        tables.insert(*b"loca", loca);
+        tables.insert(*b"hmtx", hmtx);
+        tables.insert(CMAP, cmap_table(&mapping));
+        sfnt_file([0, 1, 0, 0], &tables)
+    }
+
+    #[test]
+    fn built_fonts_parse_back_through_sfnt_and_woff() {
+        let mapping = BTreeMap::from([(u32::from('a'), 1), (u32::from('あ'), 2), (0x1F600, 3)]);
+        let mut tables = minimal_tables(4);
+        tables.insert(CMAP, cmap_table(&mapping));
+        let sfnt = sfnt_file([0, 1, 0, 0], &tables);
+        assert_eq!(checksum(&sfnt), 0xB1B0_AFBA);
+
+        let face = Face::parse(&sfnt, 0).expect("built sfnt parses");
+        assert_eq!(face.number_of_glyphs(), 4);
+        let subtables: Vec<_> = face.tables().cmap.expect("cmap").subtables.into_iter().collect();
+        assert_eq!(subtables.len(), 2);
+        for subtable in subtables {
+            let bmp_only = matches!(subtable.format, Format::SegmentMappingToDeltaValues(_));
+            for (&c, &glyph) in &mapping {
+                let expected = (!bmp_only || c <= 0xFFFF).then_some(GlyphId(glyph));
+                assert_eq!(subtable.glyph_index(c), expected, "U+{:04X} in {:?}", c, subtable.format);
+            }
+            assert_eq!(subtable.glyph_index(u32::from('b')), None);
+        }
+
+        let woff = woff_file(&sfnt).expect("woff packs");
+        let (format, unpacked) = read_tables(&woff, None).expect("woff parses");
+        assert_eq!(format, "woff");
+        // Only head.checkSumAdjustment, filled in by sfnt_file, differs from the tables put in
+        assert_eq!(unpacked.keys().collect::<Vec<_>>(), tables.keys().collect::<Vec<_>>());
+        assert_eq!(unpacked[&CMAP], tables[&CMAP]);
+        assert_eq!(sfnt_file([0, 1, 0, 0], &unpacked), sfnt);
+    }
+}
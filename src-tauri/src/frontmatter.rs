@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+
+/// A front matter value. Only the YAML subset memos actually use is understood:
+/// `key: value`, `key: [a, b]` and block lists (`- a`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrontMatterValue {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FrontMatter {
+    pub values: BTreeMap<String, FrontMatterValue>,
+    /// Byte offset where the Markdown body starts (0 when there is no front matter)
+    pub body_offset: usize,
+}
+
+impl FrontMatter {
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.values.get(key)? {
+            FrontMatterValue::Scalar(value) if !value.is_empty() => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a list value, treating a lone scalar as a one-element list
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        match self.values.get(key) {
+            Some(FrontMatterValue::List(items)) => items.clone(),
+            Some(FrontMatterValue::Scalar(value)) if !value.is_empty() => vec![value.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.get_str("title")
+    }
+
+    pub fn aliases(&self) -> Vec<String> {
+        let mut aliases = self.get_list("aliases");
+        aliases.extend(self.get_list("alias"));
+        aliases
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    let quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+    if quoted {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_inline_list(value: &str) -> Vec<String> {
+    value
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(unquote)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Parses a `---` delimited front matter block at the very start of a memo
+pub fn parse(content: &str) -> FrontMatter {
+    let mut front_matter = FrontMatter::default();
+    let mut lines = content.split_inclusive('\n');
+
+    match lines.next() {
+        Some(first) if first.trim_end() == "---" => {}
+        _ => return front_matter,
+    }
+
+    let mut offset = content.split_inclusive('\n').next().map(str::len).unwrap_or(0);
+    let mut current_key: Option<String> = None;
+
+    for line in lines {
+        offset += line.len();
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            front_matter.body_offset = offset;
+            return front_matter;
+        }
+
+        let stripped = trimmed.trim_start();
+        if let Some(item) = stripped.strip_prefix("- ") {
+            // Block list item belonging to the last key
+            if let Some(key) = &current_key {
+                let item = unquote(item);
+                match front_matter.values.get_mut(key) {
+                    Some(FrontMatterValue::List(items)) => items.push(item),
+                    _ => {
+                        front_matter
+                            .values
+                            .insert(key.clone(), FrontMatterValue::List(vec![item]));
+                    }
+                }
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = trimmed.split_once(':') {
+            if key.starts_with(char::is_whitespace) || key.is_empty() {
+                continue;
+            }
+            let key = key.trim().to_string();
+            let value = value.trim();
+            let parsed = if value.starts_with('[') && value.ends_with(']') {
+                FrontMatterValue::List(parse_inline_list(value))
+            } else {
+                FrontMatterValue::Scalar(unquote(value))
+            };
+            front_matter.values.insert(key.clone(), parsed);
+            current_key = Some(key);
+        }
+    }
+
+    // Unterminated block: treat the whole thing as body
+    FrontMatter::default()
+}
+
+/// Returns the heading level and text if the line is an ATX heading (`## Title`)
+pub fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_start();
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+/// Returns the first non-empty heading of the body, if any
+pub fn first_heading(body: &str) -> Option<&str> {
+    body.lines()
+        .filter_map(parse_heading)
+        .map(|(_, text)| text)
+        .find(|text| !text.is_empty())
+}
@@ -0,0 +1,499 @@
+use git2::{
+    DiffFormat, DiffOptions, Index, IndexEntry, IndexTime, Oid, Repository, Signature, Sort, Status, StatusOptions, Tree,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::autosave::AutosaveState;
+use crate::commands::{build_memo_meta, memo_kind, validate_memo_path, CommandError, MemoKind, MemoMeta};
+use crate::ids;
+use crate::vault::{VaultRegistry, VaultState};
+
+/// A batch is committed once no memo has been saved for this long...
+const COMMIT_DELAY: Duration = Duration::from_secs(10);
+/// ...or at the latest this long after its first change, even while still editing
+const MAX_BATCH_AGE: Duration = Duration::from_secs(120);
+const WORKER_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_LOG_LIMIT: usize = 50;
+/// Emitted with the error message when an automatic commit fails
+pub const GIT_COMMIT_FAILED_EVENT: &str = "git-commit-failed";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GitFileState {
+    New,
+    Modified,
+    Deleted,
+    Renamed,
+    Conflicted,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GitFileStatus {
+    pub path: String,
+    pub state: GitFileState,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GitStatus {
+    pub is_repository: bool,
+    pub branch: Option<String>,
+    pub auto_commit: bool,
+    /// Uncommitted memos of the working folder
+    pub files: Vec<GitFileStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GitCommit {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    /// Unix ms
+    pub time: u64,
+}
+
+struct Batch {
+    first_change: Instant,
+    last_change: Instant,
+}
+
+/// Managed state holding the working folders with changes waiting to be auto-committed
+#[derive(Default)]
+pub struct GitState(Arc<Mutex<HashMap<PathBuf, Batch>>>);
+
+fn lock_batches(batches: &Mutex<HashMap<PathBuf, Batch>>) -> MutexGuard<'_, HashMap<PathBuf, Batch>> {
+    batches.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl GitState {
+    /// Queues an auto-commit of the working folder if the vault opted in.
+    /// Called after every change made through the app.
+    pub fn record_change(&self, registry: &VaultRegistry) {
+        let (Ok(settings), Ok(folder)) = (registry.active_settings(), registry.active_folder()) else {
+            return;
+        };
+        if !settings.git_auto_commit {
+            return;
+        }
+        let now = Instant::now();
+        lock_batches(&self.0)
+            .entry(folder)
+            .and_modify(|batch| batch.last_change = now)
+            .or_insert(Batch {
+                first_change: now,
+                last_change: now,
+            });
+    }
+
+    /// Starts the background thread committing batches that are ready.
+    /// Called once from `run()` at startup.
+    pub fn start_worker(&self, app: AppHandle) {
+        let batches = Arc::clone(&self.0);
+        thread::spawn(move || loop {
+            thread::sleep(WORKER_INTERVAL);
+            let now = Instant::now();
+            let ready: Vec<PathBuf> = {
+                let mut batches = lock_batches(&batches);
+                let ready: Vec<PathBuf> = batches
+                    .iter()
+                    .filter(|(_, batch)| {
+                        now - batch.last_change >= COMMIT_DELAY || now - batch.first_change >= MAX_BATCH_AGE
+                    })
+                    .map(|(folder, _)| folder.clone())
+                    .collect();
+                for folder in &ready {
+                    batches.remove(folder);
+                }
+                ready
+            };
+            for folder in ready {
+                match commit_folder(&folder) {
+                    // Auto-commit is simply inactive for a folder outside any repository
+                    Ok(()) | Err(CommandError::NotARepository) => {}
+                    Err(error) => {
+                        let _ = app.emit(GIT_COMMIT_FAILED_EVENT, error.to_string());
+                    }
+                }
+            }
+        });
+    }
+}
+
+impl From<git2::Error> for CommandError {
+    fn from(error: git2::Error) -> Self {
+        CommandError::GitError(error.message().to_string())
+    }
+}
+
+/// The repository containing the working folder, and the folder's path inside it
+fn open_repository(folder: &Path) -> Result<(Repository, PathBuf), CommandError> {
+    let repo = Repository::discover(folder).map_err(|_| CommandError::NotARepository)?;
+    let workdir = repo
+        .workdir()
+        .ok_or(CommandError::NotARepository)?
+        .canonicalize()?;
+    let relative = folder
+        .strip_prefix(&workdir)
+        .map_err(|_| CommandError::NotARepository)?
+        .to_path_buf();
+    Ok((repo, relative))
+}
+
+/// Repository path of a memo, always with `/` separators
+fn repo_path(relative_folder: &Path, file: &Path) -> String {
+    relative_folder
+        .join(file.file_name().unwrap_or_default())
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Only memos directly inside the working folder are committed; other files in the repository are left alone
+fn is_folder_memo(relative_folder: &Path, path: &Path) -> bool {
+    path.parent() == Some(relative_folder) && memo_kind(path).is_ok()
+}
+
+fn folder_pathspec(relative_folder: &Path) -> String {
+    if relative_folder.as_os_str().is_empty() {
+        "*".to_string()
+    } else {
+        format!("{}/*", relative_folder.to_string_lossy().replace('\\', "/"))
+    }
+}
+
+fn head_tree(repo: &Repository) -> Option<Tree<'_>> {
+    repo.head().ok()?.peel_to_tree().ok()
+}
+
+/// Builds the tree to auto-commit: HEAD's tree with the working folder's memos replaced by their
+/// current content. The repository's index isn't used, so whatever the user has staged
+/// elsewhere stays out of the commit.
+fn memo_tree<'r>(repo: &'r Repository, folder: &Path, relative: &Path) -> Result<Tree<'r>, CommandError> {
+    let mut index = Index::new()?;
+    if let Some(tree) = head_tree(repo) {
+        index.read_tree(&tree)?;
+    }
+
+    let tracked: Vec<PathBuf> = index
+        .iter()
+        .map(|entry| PathBuf::from(String::from_utf8_lossy(&entry.path).to_string()))
+        .filter(|path| is_folder_memo(relative, path))
+        .collect();
+    for path in tracked {
+        if !folder.join(path.file_name().unwrap_or_default()).is_file() {
+            index.remove_path(&path)?;
+        }
+    }
+
+    for entry in fs::read_dir(folder)?.flatten() {
+        let file = entry.path();
+        let path = relative.join(entry.file_name());
+        if !file.is_file() || !is_folder_memo(relative, &path) {
+            continue;
+        }
+        let existing = index.get_path(&path, 0);
+        // Untracked memos the repository ignores stay uncommitted, as with `git add`
+        if existing.is_none() && repo.is_path_ignored(&path)? {
+            continue;
+        }
+        let content = fs::read(&file)?;
+        index.add(&IndexEntry {
+            ctime: IndexTime::new(0, 0),
+            mtime: IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: existing.map_or(0o100644, |entry| entry.mode),
+            uid: 0,
+            gid: 0,
+            file_size: content.len() as u32,
+            id: repo.blob(&content)?,
+            flags: 0,
+            flags_extended: 0,
+            path: repo_path(relative, &path).into_bytes(),
+        })?;
+    }
+    Ok(repo.find_tree(index.write_tree_to(repo)?)?)
+}
+
+fn commit_folder(folder: &Path) -> Result<(), CommandError> {
+    let (repo, relative) = open_repository(folder)?;
+    let tree = memo_tree(&repo, folder, &relative)?;
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let old_tree = parent.as_ref().map(|commit| commit.tree()).transpose()?;
+    let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&tree), None)?;
+    let changed: Vec<PathBuf> = diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()).map(Path::to_path_buf))
+        .collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    let names: Vec<String> = changed
+        .iter()
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+        .collect();
+    let message = match names.as_slice() {
+        [name] => format!("Update {}", name),
+        _ => format!("Update {} memos\n\n{}", names.len(), names.join("\n")),
+    };
+    // Fall back to a generic identity when the repository has no user configured
+    let signature = repo.signature().or_else(|_| Signature::now("Memo", "memo@localhost"))?;
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &parents)?;
+
+    // Bring the committed memos up to date in the repository's index, so they don't show up as
+    // staged changes against the new HEAD; other entries are left as the user staged them
+    let mut index = repo.index()?;
+    for path in &changed {
+        if folder.join(path.file_name().unwrap_or_default()).is_file() {
+            index.add_path(path)?;
+        } else if index.get_path(path, 0).is_some() {
+            index.remove_path(path)?;
+        }
+    }
+    index.write()?;
+    Ok(())
+}
+
+/// Resolves a memo path for history commands. Unlike `validate_memo_path`, the file may
+/// have been deleted, as long as it would sit directly in the working folder.
+fn resolve_history_path(folder: &Path, file_path: &str) -> Result<(PathBuf, MemoKind), CommandError> {
+    let path = PathBuf::from(file_path);
+    if path.exists() {
+        return validate_memo_path(folder, file_path);
+    }
+    let kind = memo_kind(&path)?;
+    let parent = path.parent().ok_or_else(|| CommandError::PathError(file_path.to_string()))?;
+    // Security: The parent must be the working folder itself
+    if parent.canonicalize()? != folder {
+        return Err(CommandError::AccessDenied("file is outside working folder".to_string()));
+    }
+    Ok((path, kind))
+}
+
+fn diff_to_string(diff: &git2::Diff<'_>) -> Result<String, CommandError> {
+    let mut patch = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    Ok(patch)
+}
+
+#[tauri::command]
+pub fn git_status(state: State<'_, VaultState>) -> Result<GitStatus, CommandError> {
+    let registry = state.lock();
+    let folder = registry.active_folder()?;
+    let auto_commit = registry.active_settings()?.git_auto_commit;
+
+    let Ok((repo, relative)) = open_repository(&folder) else {
+        return Ok(GitStatus {
+            is_repository: false,
+            branch: None,
+            auto_commit,
+            files: Vec::new(),
+        });
+    };
+    let branch = repo.head().ok().and_then(|head| head.shorthand().ok().map(str::to_string));
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .pathspec(folder_pathspec(&relative));
+    let files = repo
+        .statuses(Some(&mut options))?
+        .iter()
+        .filter_map(|entry| {
+            let path = PathBuf::from(entry.path().ok()?);
+            if !is_folder_memo(&relative, &path) {
+                return None;
+            }
+            let status = entry.status();
+            let state = if status.is_conflicted() {
+                GitFileState::Conflicted
+            } else if status.intersects(Status::INDEX_RENAMED | Status::WT_RENAMED) {
+                GitFileState::Renamed
+            } else if status.intersects(Status::INDEX_NEW | Status::WT_NEW) {
+                GitFileState::New
+            } else if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+                GitFileState::Deleted
+            } else if status.intersects(Status::INDEX_MODIFIED | Status::WT_MODIFIED | Status::INDEX_TYPECHANGE | Status::WT_TYPECHANGE) {
+                GitFileState::Modified
+            } else {
+                return None;
+            };
+            Some(GitFileStatus {
+                path: folder.join(path.file_name()?).to_string_lossy().to_string(),
+                state,
+            })
+        })
+        .collect();
+
+    Ok(GitStatus {
+        is_repository: true,
+        branch,
+        auto_commit,
+        files,
+    })
+}
+
+/// Lists commits newest first, only those touching `file_path` if given (else the working folder)
+#[tauri::command]
+pub fn git_log(
+    state: State<'_, VaultState>,
+    file_path: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<GitCommit>, CommandError> {
+    let folder = state.lock().active_folder()?;
+    let (repo, relative) = open_repository(&folder)?;
+    let pathspec = match file_path.as_deref() {
+        Some(file_path) => repo_path(&relative, &resolve_history_path(&folder, file_path)?.0),
+        None => folder_pathspec(&relative),
+    };
+
+    let mut walk = repo.revwalk()?;
+    if walk.push_head().is_err() {
+        // No commits yet
+        return Ok(Vec::new());
+    }
+    walk.set_sorting(Sort::TIME)?;
+
+    let mut commits = Vec::new();
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        let parent_tree = commit.parents().next().map(|parent| parent.tree()).transpose()?;
+        let mut options = DiffOptions::new();
+        options.pathspec(&pathspec);
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), Some(&mut options))?;
+        if diff.deltas().len() == 0 {
+            continue;
+        }
+
+        commits.push(GitCommit {
+            id: commit.id().to_string(),
+            summary: commit.summary().ok().flatten().unwrap_or_default().to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            time: u64::try_from(commit.time().seconds()).unwrap_or(0) * 1000,
+        });
+        if commits.len() >= limit.unwrap_or(DEFAULT_LOG_LIMIT) {
+            break;
+        }
+    }
+    Ok(commits)
+}
+
+/// Returns a unified diff. With `commit`, the changes that commit made; otherwise the
+/// uncommitted changes. Limited to `file_path` if given, else the working folder.
+#[tauri::command]
+pub fn git_diff(
+    state: State<'_, VaultState>,
+    file_path: Option<String>,
+    commit: Option<String>,
+) -> Result<String, CommandError> {
+    let folder = state.lock().active_folder()?;
+    let (repo, relative) = open_repository(&folder)?;
+
+    let mut options = DiffOptions::new();
+    match file_path.as_deref() {
+        Some(file_path) => options.pathspec(repo_path(&relative, &resolve_history_path(&folder, file_path)?.0)),
+        None => options.pathspec(folder_pathspec(&relative)),
+    };
+
+    let diff = match commit.as_deref() {
+        Some(commit) => {
+            let commit = repo.find_commit(Oid::from_str(commit)?)?;
+            let parent_tree = commit.parents().next().map(|parent| parent.tree()).transpose()?;
+            repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), Some(&mut options))?
+        }
+        None => {
+            options.include_untracked(true).show_untracked_content(true);
+            repo.diff_tree_to_workdir_with_index(head_tree(&repo).as_ref(), Some(&mut options))?
+        }
+    };
+    diff_to_string(&diff)
+}
+
+/// Restores a memo to its content at `commit` (default: the last commit), also bringing
+/// back deleted memos. The restored file is left uncommitted.
+#[tauri::command]
+pub fn git_restore(
+    state: State<'_, VaultState>,
+    git: State<'_, GitState>,
+    autosave: State<'_, AutosaveState>,
+    file_path: String,
+    commit: Option<String>,
+) -> Result<MemoMeta, CommandError> {
+    let folder = state.lock().active_folder()?;
+    let (path, kind) = resolve_history_path(&folder, &file_path)?;
+    let (repo, relative) = open_repository(&folder)?;
+
+    let commit = match commit.as_deref() {
+        Some(commit) => repo.find_commit(Oid::from_str(commit)?)?,
+        None => repo.head()?.peel_to_commit()?,
+    };
+    let entry = commit
+        .tree()?
+        .get_path(Path::new(&repo_path(&relative, &path)))
+        .map_err(|_| CommandError::FileNotFound(file_path.clone()))?;
+    let blob = entry.to_object(&repo)?.peel_to_blob()?;
+    // The restored content wins over edits still queued for autosave
+    autosave.discard(&folder, &file_path);
+    fs::write(&path, blob.content())?;
+
+    let mut meta = build_memo_meta(&path, kind);
+    meta.path = file_path;
+    // Bookkeeping only: the memo is already restored
+    let registry = state.lock();
+    let _ = ids::assign_id(&registry, &mut meta);
+    git.record_change(&registry);
+    Ok(meta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_commit_leaves_other_staged_files_alone() {
+        let root = std::env::temp_dir().join(format!("memo-git-test-{}", uuid::Uuid::new_v4()));
+        let folder = root.join("notes");
+        fs::create_dir_all(&folder).unwrap();
+        let repo = Repository::init(&root).unwrap();
+        fs::write(folder.join("memo.md"), "first").unwrap();
+        fs::write(root.join("code.rs"), "fn main() {}").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("code.rs")).unwrap();
+        index.write().unwrap();
+
+        let folder = folder.canonicalize().unwrap();
+        commit_folder(&folder).unwrap();
+        let status = |path: &str| {
+            let statuses = repo.statuses(None).unwrap();
+            let status = statuses.iter().find(|entry| entry.path().ok() == Some(path)).map(|entry| entry.status());
+            status
+        };
+        let head = head_tree(&repo).unwrap();
+        assert!(head.get_path(Path::new("notes/memo.md")).is_ok());
+        assert!(head.get_path(Path::new("code.rs")).is_err());
+        assert_eq!(status("code.rs"), Some(Status::INDEX_NEW));
+        assert_eq!(status("notes/memo.md"), None);
+
+        fs::remove_file(folder.join("memo.md")).unwrap();
+        commit_folder(&folder).unwrap();
+        assert!(head_tree(&repo).unwrap().get_path(Path::new("notes/memo.md")).is_err());
+        assert_eq!(status("notes/memo.md"), None);
+        assert_eq!(status("code.rs"), Some(Status::INDEX_NEW));
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,368 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::commands::{build_memo_meta, scan_memos, validate_memo_path, CommandError, MemoMeta};
+use crate::pins;
+use crate::session;
+use crate::storage::{read_json, write_json};
+use crate::vault::{VaultRegistry, VaultState};
+
+/// Sidecar mapping of memo IDs, kept in the vault's backend data directory so memo files are never touched
+const IDS_FILE: &str = "ids.json";
+/// Bytes hashed to tell apart memos with the same size and modification time
+const FINGERPRINT_SIZE: u64 = 4 * 1024;
+
+/// What is known about the file behind an ID, used to recognise it after an outside rename
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct IdEntry {
+    path: String,
+    #[serde(default)]
+    inode: Option<u64>,
+    size: u64,
+    modified_at: u64,
+    fingerprint: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct MemoIds {
+    entries: BTreeMap<String, IdEntry>,
+}
+
+#[cfg(unix)]
+fn file_inode(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|m| m.ino())
+}
+
+#[cfg(not(unix))]
+fn file_inode(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// FNV-1a over the head of the file. Stable across builds, unlike `DefaultHasher`.
+fn content_fingerprint(path: &Path) -> u64 {
+    let mut head = Vec::new();
+    if let Ok(file) = fs::File::open(path) {
+        let _ = file.take(FINGERPRINT_SIZE).read_to_end(&mut head);
+    }
+    head.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl IdEntry {
+    fn new(memo: &MemoMeta) -> Self {
+        let path = Path::new(&memo.path);
+        IdEntry {
+            path: memo.path.clone(),
+            inode: file_inode(path),
+            size: memo.size,
+            modified_at: memo.modified_at,
+            fingerprint: content_fingerprint(path),
+        }
+    }
+
+    fn is_current(&self, memo: &MemoMeta) -> bool {
+        self.path == memo.path && self.size == memo.size && self.modified_at == memo.modified_at
+    }
+
+    /// A rename keeps the inode (where there is one) as well as size, mtime and content. The
+    /// inode alone isn't enough: filesystems hand a deleted file's inode to the next new file.
+    fn is_same_file(&self, memo: &MemoMeta) -> bool {
+        let path = Path::new(&memo.path);
+        let unchanged = self.size == memo.size && self.modified_at == memo.modified_at;
+        match (self.inode, file_inode(path)) {
+            (Some(inode), Some(current)) => {
+                inode == current && (unchanged || self.fingerprint == content_fingerprint(path))
+            }
+            _ => unchanged && self.fingerprint == content_fingerprint(path),
+        }
+    }
+}
+
+fn ids_file(registry: &VaultRegistry) -> Result<PathBuf, CommandError> {
+    Ok(registry.active_vault_dir()?.join(IDS_FILE))
+}
+
+/// Looks up or assigns the ID of each memo. A memo at an unknown path that matches an entry
+/// whose file is gone was renamed outside the app and keeps its ID. With `prune`, entries of
+/// files that no longer exist are dropped. Returns whether `ids` changed and the renames found.
+fn match_ids(ids: &mut MemoIds, memos: &mut [MemoMeta], prune: bool) -> (bool, Vec<(String, String)>) {
+    let mut by_path: HashMap<String, String> = ids
+        .entries
+        .iter()
+        .map(|(id, entry)| (entry.path.clone(), id.clone()))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut moves = Vec::new();
+    let mut changed = false;
+
+    for memo in memos.iter_mut() {
+        let id = match by_path.get(&memo.path) {
+            Some(id) => id.clone(),
+            None => {
+                let moved = ids
+                    .entries
+                    .iter()
+                    .filter(|(id, entry)| !seen.contains(*id) && !Path::new(&entry.path).exists())
+                    .find(|(_, entry)| entry.is_same_file(memo))
+                    .map(|(id, entry)| (id.clone(), entry.path.clone()));
+                match moved {
+                    Some((id, old_path)) => {
+                        moves.push((old_path.clone(), memo.path.clone()));
+                        by_path.remove(&old_path);
+                        id
+                    }
+                    None => uuid::Uuid::new_v4().to_string(),
+                }
+            }
+        };
+
+        // Saves only change size and mtime, so they don't rewrite ids.json. Full listings refresh
+        // stale entries, keeping what renames are recognised by reasonably up to date.
+        let refresh = match ids.entries.get(&id) {
+            Some(entry) if entry.path == memo.path => prune && !entry.is_current(memo),
+            _ => true,
+        };
+        if refresh {
+            ids.entries.insert(id.clone(), IdEntry::new(memo));
+            by_path.insert(memo.path.clone(), id.clone());
+            changed = true;
+        }
+        seen.insert(id.clone());
+        memo.id = id;
+    }
+
+    if prune {
+        let before = ids.entries.len();
+        ids.entries
+            .retain(|id, entry| seen.contains(id) || Path::new(&entry.path).exists());
+        changed |= ids.entries.len() != before;
+    }
+    (changed, moves)
+}
+
+/// Fills in the IDs of memos and saves what changed. A memo renamed outside the app keeps its
+/// ID (and with it its pin), and its recent entry follows it.
+fn reconcile(registry: &VaultRegistry, memos: &mut [MemoMeta], prune: bool) -> Result<(), CommandError> {
+    let file = ids_file(registry)?;
+    let mut ids: MemoIds = read_json(&file)?;
+    let (changed, moves) = match_ids(&mut ids, memos, prune);
+    if changed {
+        write_json(&file, &ids)?;
+    }
+
+    // Bookkeeping only: a failure here must not hide the memos themselves
+    for (old_path, new_path) in moves {
+        let _ = session::rename_recent(registry, &old_path, &new_path);
+    }
+    Ok(())
+}
+
+/// Fills in the IDs of some memos of the working folder
+pub fn assign_ids(registry: &VaultRegistry, memos: &mut [MemoMeta]) -> Result<(), CommandError> {
+    reconcile(registry, memos, false)
+}
+
+/// Fills in the ID of a single memo of the working folder
+pub fn assign_id(registry: &VaultRegistry, memo: &mut MemoMeta) -> Result<(), CommandError> {
+    reconcile(registry, std::slice::from_mut(memo), false)
+}
+
+/// Fills in the IDs of a full listing of the working folder and forgets deleted memos
+pub fn sync_ids(registry: &VaultRegistry, memos: &mut [MemoMeta]) -> Result<(), CommandError> {
+    reconcile(registry, memos, true)
+}
+
+/// Fills in the IDs `ids.json` already has for these memos, without assigning new ones or
+/// writing anything. Memos no listing has seen yet keep an empty ID.
+pub fn known_ids(registry: &VaultRegistry, memos: &mut [MemoMeta]) -> Result<(), CommandError> {
+    let ids: MemoIds = read_json(&ids_file(registry)?)?;
+    let by_path: HashMap<&str, &str> = ids
+        .entries
+        .iter()
+        .map(|(id, entry)| (entry.path.as_str(), id.as_str()))
+        .collect();
+    for memo in memos.iter_mut() {
+        if let Some(id) = by_path.get(memo.path.as_str()) {
+            memo.id = id.to_string();
+        }
+    }
+    Ok(())
+}
+
+/// Moves an ID along with a rename done through the app
+pub fn rename_id(registry: &VaultRegistry, old_path: &str, new_path: &str) -> Result<(), CommandError> {
+    let file = ids_file(registry)?;
+    let mut ids: MemoIds = read_json(&file)?;
+    let Some(entry) = ids.entries.values_mut().find(|entry| entry.path == old_path) else {
+        return Ok(());
+    };
+    entry.path = new_path.to_string();
+    write_json(&file, &ids)
+}
+
+/// Drops the ID of a deleted memo and returns it
+pub fn forget_id(registry: &VaultRegistry, path: &str) -> Result<Option<String>, CommandError> {
+    let file = ids_file(registry)?;
+    let mut ids: MemoIds = read_json(&file)?;
+    let Some(id) = ids.entries.iter().find(|(_, entry)| entry.path == path).map(|(id, _)| id.clone()) else {
+        return Ok(None);
+    };
+    ids.entries.remove(&id);
+    write_json(&file, &ids)?;
+    Ok(Some(id))
+}
+
+/// Last known path of each ID. A memo renamed outside the app keeps its old path here until
+/// the next listing notices the rename.
+pub fn known_paths(registry: &VaultRegistry) -> Result<HashMap<String, String>, CommandError> {
+    let ids: MemoIds = read_json(&ids_file(registry)?)?;
+    Ok(ids.entries.into_iter().map(|(id, entry)| (id, entry.path)).collect())
+}
+
+fn lookup(registry: &VaultRegistry, id: &str) -> Result<Option<String>, CommandError> {
+    Ok(known_paths(registry)?.remove(id))
+}
+
+/// Returns the current metadata of the memo with the given ID. If its file has moved since
+/// the last listing, the working folder is rescanned to find it.
+#[tauri::command]
+pub fn resolve_memo_id(state: State<'_, VaultState>, id: String) -> Result<MemoMeta, CommandError> {
+    let registry = state.lock();
+    let folder = registry.active_folder()?;
+
+    let path = match lookup(&registry, &id)?.filter(|path| Path::new(path).is_file()) {
+        Some(path) => path,
+        None => {
+            let mut memos = scan_memos(&folder, &registry.active_settings()?.extensions)?;
+            sync_ids(&registry, &mut memos)?;
+            memos
+                .into_iter()
+                .find(|memo| memo.id == id)
+                .map(|memo| memo.path)
+                .ok_or_else(|| CommandError::FileNotFound(format!("memo {}", id)))?
+        }
+    };
+
+    // Security: Validate extension and that the file is within the working folder
+    let (memo_path, kind) = validate_memo_path(&folder, &path)?;
+    let mut meta = build_memo_meta(&memo_path, kind);
+    meta.path = path;
+    meta.pinned = pins::pinned_ids(&registry).is_ok_and(|pinned| pinned.contains(&id));
+    meta.id = id;
+    Ok(meta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::MemoKind;
+
+    struct Folder(PathBuf);
+
+    impl Folder {
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!("memo-ids-test-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&root).unwrap();
+            Folder(root)
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.0.join(name)
+        }
+
+        fn listing(&self) -> Vec<MemoMeta> {
+            let mut paths: Vec<PathBuf> = fs::read_dir(&self.0).unwrap().map(|entry| entry.unwrap().path()).collect();
+            paths.sort();
+            paths.iter().map(|path| build_memo_meta(path, MemoKind::Markdown)).collect()
+        }
+
+        /// Lists the folder like `sync_ids` does and returns each memo's ID by file name
+        fn sync(&self, ids: &mut MemoIds) -> BTreeMap<String, String> {
+            let mut memos = self.listing();
+            match_ids(ids, &mut memos, true);
+            memos
+                .into_iter()
+                .map(|memo| (Path::new(&memo.path).file_name().unwrap().to_string_lossy().into_owned(), memo.id))
+                .collect()
+        }
+    }
+
+    impl Drop for Folder {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn a_renamed_memo_keeps_its_id() {
+        let folder = Folder::new();
+        fs::write(folder.path("a.md"), "# Alpha").unwrap();
+        let mut ids = MemoIds::default();
+        let before = folder.sync(&mut ids);
+
+        fs::rename(folder.path("a.md"), folder.path("b.md")).unwrap();
+        let mut memos = folder.listing();
+        let (changed, moves) = match_ids(&mut ids, &mut memos, true);
+
+        assert!(changed);
+        assert_eq!(memos[0].id, before["a.md"]);
+        let old_path = folder.path("a.md").to_string_lossy().into_owned();
+        assert_eq!(moves, [(old_path, memos[0].path.clone())]);
+        assert_eq!(ids.entries.len(), 1);
+    }
+
+    #[test]
+    fn a_delete_and_a_create_get_a_new_id() {
+        let folder = Folder::new();
+        fs::write(folder.path("a.md"), "# Alpha").unwrap();
+        let mut ids = MemoIds::default();
+        let before = folder.sync(&mut ids);
+
+        fs::remove_file(folder.path("a.md")).unwrap();
+        fs::write(folder.path("b.md"), "# Something else entirely").unwrap();
+        let mut memos = folder.listing();
+        let (_, moves) = match_ids(&mut ids, &mut memos, true);
+
+        assert_ne!(memos[0].id, before["a.md"]);
+        assert!(moves.is_empty());
+        // The deleted memo's entry is pruned
+        assert_eq!(ids.entries.keys().collect::<Vec<_>>(), [&memos[0].id]);
+    }
+
+    #[test]
+    fn two_renames_in_one_pass_keep_their_own_ids() {
+        let folder = Folder::new();
+        fs::write(folder.path("a.md"), "# Alpha").unwrap();
+        fs::write(folder.path("b.md"), "# Beta, which is longer").unwrap();
+        let mut ids = MemoIds::default();
+        let before = folder.sync(&mut ids);
+
+        fs::rename(folder.path("a.md"), folder.path("d.md")).unwrap();
+        fs::rename(folder.path("b.md"), folder.path("c.md")).unwrap();
+        let after = folder.sync(&mut ids);
+
+        assert_eq!(after["d.md"], before["a.md"]);
+        assert_eq!(after["c.md"], before["b.md"]);
+        assert_eq!(ids.entries.len(), 2);
+    }
+
+    #[test]
+    fn an_unchanged_listing_writes_nothing() {
+        let folder = Folder::new();
+        fs::write(folder.path("a.md"), "# Alpha").unwrap();
+        let mut ids = MemoIds::default();
+        let before = folder.sync(&mut ids);
+
+        let mut memos = folder.listing();
+        let (changed, moves) = match_ids(&mut ids, &mut memos, true);
+        assert!(!changed);
+        assert!(moves.is_empty());
+        assert_eq!(memos[0].id, before["a.md"]);
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::commands::{build_memo_meta, MemoMeta, DEFAULT_MAX_MEMO_SIZE};
+use crate::crypto;
+use crate::encoding;
+use crate::frontmatter;
+use crate::stats::{self, MemoSummary};
+use crate::tasks::{self, TaskItem};
+
+/// Only the head of a memo is needed for its title and front matter
+const HEAD_SIZE: u64 = 64 * 1024;
+/// A listing is reused while its folder looks unchanged, but no longer than this, so edits to
+/// ignore files and `.memo/config.toml` are picked up too
+const LISTING_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Data extracted from a memo's content, cached until the file changes
+#[derive(Debug, Clone, Default)]
+pub struct IndexedMemo {
+    pub title: Option<String>,
+    pub aliases: Vec<String>,
+    /// Front matter `due:` (or `remind:`) date, unparsed
+    pub due: Option<String>,
+}
+
+struct CacheEntry {
+    modified_at: u64,
+    size: u64,
+    memo: IndexedMemo,
+    /// These need the whole file, so they are only computed when asked for
+    summary: Option<MemoSummary>,
+    tasks: Option<Vec<TaskItem>>,
+}
+
+impl CacheEntry {
+    fn new(meta: &MemoMeta) -> Self {
+        CacheEntry {
+            modified_at: meta.modified_at,
+            size: meta.size,
+            memo: index_file(Path::new(&meta.path)),
+            summary: None,
+            tasks: None,
+        }
+    }
+
+    fn is_current(&self, meta: &MemoMeta) -> bool {
+        self.modified_at == meta.modified_at && self.size == meta.size
+    }
+}
+
+/// The last full listing of a vault's folder, with IDs filled in
+struct Listing {
+    folder: PathBuf,
+    extensions: Vec<String>,
+    folder_modified: Option<SystemTime>,
+    memos: Vec<MemoMeta>,
+    at: Instant,
+}
+
+/// Modification time of a folder, which changes when a memo in it is created, deleted or renamed.
+/// Taken before scanning, so a change during the scan makes the listing stale.
+pub fn folder_modified(folder: &Path) -> Option<SystemTime> {
+    fs::metadata(folder).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// In-memory index of a vault, keyed by memo path and invalidated by mtime/size
+#[derive(Default)]
+pub struct MemoIndex {
+    entries: HashMap<String, CacheEntry>,
+    listing: Option<Listing>,
+}
+
+impl MemoIndex {
+    /// Brings the index in line with a fresh listing and returns the entries in listing order.
+    /// Memos that are no longer listed (deleted, renamed or excluded) are dropped.
+    pub fn refresh(&mut self, memos: &[MemoMeta]) -> Vec<IndexedMemo> {
+        let mut entries = HashMap::with_capacity(memos.len());
+        let mut indexed = Vec::with_capacity(memos.len());
+
+        for meta in memos {
+            let entry = match self.entries.remove(&meta.path) {
+                Some(entry) if entry.is_current(meta) => entry,
+                _ => CacheEntry::new(meta),
+            };
+            indexed.push(entry.memo.clone());
+            entries.insert(meta.path.clone(), entry);
+        }
+
+        self.entries = entries;
+        indexed
+    }
+
+    /// Remembers a full listing for `listing`. `folder_modified` is from before the scan.
+    pub fn set_listing(&mut self, folder: &Path, extensions: &[String], folder_modified: Option<SystemTime>, memos: &[MemoMeta]) {
+        self.listing = Some(Listing {
+            folder: folder.to_path_buf(),
+            extensions: extensions.to_vec(),
+            folder_modified,
+            memos: memos.to_vec(),
+            at: Instant::now(),
+        });
+    }
+
+    /// The memos of the last full listing with fresh metadata, or `None` when it is stale: the
+    /// folder or extensions differ, the folder changed since, or it is older than `LISTING_TTL`.
+    /// Only stats the memos, so callers can skip rescanning the folder and syncing IDs.
+    pub fn listing(&self, folder: &Path, extensions: &[String], folder_modified: Option<SystemTime>) -> Option<Vec<MemoMeta>> {
+        let listing = self.listing.as_ref().filter(|listing| {
+            listing.folder == folder
+                && listing.extensions == extensions
+                && listing.folder_modified.is_some()
+                && listing.folder_modified == folder_modified
+                && listing.at.elapsed() < LISTING_TTL
+        })?;
+        let memos = listing
+            .memos
+            .iter()
+            .map(|memo| MemoMeta {
+                id: memo.id.clone(),
+                path: memo.path.clone(),
+                pinned: memo.pinned,
+                ..build_memo_meta(&PathBuf::from(&memo.path), memo.kind)
+            })
+            .collect();
+        Some(memos)
+    }
+
+    /// Returns the excerpt and statistics of a memo, reading it only if it changed since the last call
+    pub fn summary(&mut self, meta: &MemoMeta) -> Option<MemoSummary> {
+        let entry = self.current_entry(meta);
+        if entry.summary.is_none() {
+            entry.summary = read_content(meta).map(|content| stats::summarize(&content, meta.kind));
+        }
+        entry.summary.clone()
+    }
+
+    /// Returns the task items of a memo, reading it only if it changed since the last call
+    pub fn tasks(&mut self, meta: &MemoMeta) -> Vec<TaskItem> {
+        let entry = self.current_entry(meta);
+        entry
+            .tasks
+            .get_or_insert_with(|| read_content(meta).map(|content| tasks::parse_tasks(&content)).unwrap_or_default())
+            .clone()
+    }
+
+    fn current_entry(&mut self, meta: &MemoMeta) -> &mut CacheEntry {
+        let entry = self
+            .entries
+            .entry(meta.path.clone())
+            .or_insert_with(|| CacheEntry::new(meta));
+        if !entry.is_current(meta) {
+            *entry = CacheEntry::new(meta);
+        }
+        entry
+    }
+}
+
+fn read_head(path: &Path) -> Option<String> {
+    let mut head = Vec::new();
+    fs::File::open(path).ok()?.take(HEAD_SIZE).read_to_end(&mut head).ok()?;
+    let is_complete = (head.len() as u64) < HEAD_SIZE;
+    let (text_encoding, bom_len) = encoding::detect_sample_encoding(&head, is_complete);
+    let (text, _) = encoding::decode_chunk(&head[bom_len..], text_encoding, is_complete).ok()?;
+    Some(text)
+}
+
+fn index_file(path: &Path) -> IndexedMemo {
+    // Unreadable or undecodable files are still listed, just without extracted data.
+    // Encrypted memos are never indexed, so nothing of their content is kept in memory.
+    let content = if crypto::is_encrypted(path) { None } else { read_head(path) };
+    let Some(content) = content else {
+        return IndexedMemo::default();
+    };
+    let front_matter = frontmatter::parse(&content);
+    let title = front_matter
+        .title()
+        .or_else(|| frontmatter::first_heading(&content[front_matter.body_offset..]))
+        .map(str::to_string);
+
+    IndexedMemo {
+        title,
+        aliases: front_matter.aliases(),
+        due: front_matter
+            .get_str("due")
+            .or_else(|| front_matter.get_str("remind"))
+            .map(str::to_string),
+    }
+}
+
+/// Reads a whole memo. Memos too large to read in one piece, encrypted or that can't be decoded, give `None`.
+fn read_content(meta: &MemoMeta) -> Option<String> {
+    if meta.size > DEFAULT_MAX_MEMO_SIZE || meta.encrypted {
+        return None;
+    }
+    let decoded = encoding::decode(&fs::read(&meta.path).ok()?).ok()?;
+    Some(decoded.content)
+}
+
+/// Managed state holding an index per vault, keyed by vault ID, so switching back and forth
+/// between vaults doesn't reread their memos
+#[derive(Default)]
+pub struct IndexState(Mutex<HashMap<String, MemoIndex>>);
+
+impl IndexState {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, MemoIndex>> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Runs `f` on the index of a vault, starting an empty one if needed
+    pub fn with<R>(&self, vault_id: &str, f: impl FnOnce(&mut MemoIndex) -> R) -> R {
+        f(self.lock().entry(vault_id.to_string()).or_default())
+    }
+
+    /// Drops the index of a removed vault
+    pub fn forget(&self, vault_id: &str) {
+        self.lock().remove(vault_id);
+    }
+}
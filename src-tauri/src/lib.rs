@@ -1,8 +1,52 @@
+mod autosave;
 mod commands;
+mod crypto;
+mod encoding;
+mod export;
+mod folder_config;
+mod fonts;
+mod frontmatter;
+mod git;
+mod ids;
+mod index;
+mod listing;
+mod pdf;
+mod pins;
+mod quick_open;
+mod reminders;
+mod session;
+mod settings;
+mod stats;
+mod storage;
+mod sync;
+mod tasks;
+mod vault;
 
+use tauri::{Manager, RunEvent, WindowEvent};
+use autosave::{flush_saves, queue_save, AutosaveState};
 use commands::{
-    create_memo, delete_installed_font, delete_memo, get_installed_font_path, install_font,
-    list_memos, pick_font_file, read_memo, rename_memo, save_memo, select_folder,
+    create_memo, delete_installed_font, delete_memo, get_installed_font, install_font,
+    list_installed_fonts, list_memos, pick_font_file, read_memo, read_memo_range, rename_memo,
+    save_memo, select_folder,
+};
+use crypto::{decrypt_memo, encrypt_memo, encryption_status, lock_encryption, unlock_encryption, CryptoState};
+use export::{export_html, export_pdf};
+use folder_config::get_folder_config;
+use fonts::{list_system_fonts, FontState, FONT_PROTOCOL};
+use git::{git_diff, git_log, git_restore, git_status, GitState};
+use ids::resolve_memo_id;
+use index::IndexState;
+use pins::{list_pinned, pin_memo, unpin_memo};
+use quick_open::quick_open;
+use reminders::{list_reminders, snooze_reminder};
+use session::{get_recent_memos, record_opened, restore_session, SessionState};
+use settings::{get_settings, update_settings, SettingsState};
+use storage::{take_startup_errors, StartupErrors};
+use sync::{clear_sync, get_sync_config, pick_sync_folder, set_sync_webdav, sync_now, SyncState};
+use tasks::{list_tasks, toggle_task};
+use vault::{
+    add_vault, get_vault_settings, list_vaults, remove_vault, switch_vault, update_vault_settings,
+    VaultRegistry, VaultState,
 };
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -10,19 +54,104 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .register_asynchronous_uri_scheme_protocol(FONT_PROTOCOL, |ctx, request, responder| {
+            // Font files run to megabytes; reading them must not hold up the main thread
+            let app = ctx.app_handle().clone();
+            tauri::async_runtime::spawn_blocking(move || responder.respond(fonts::serve_font(&app, &request)));
+        })
+        .setup(|app| {
+            let data_dir = storage::app_data_dir(app.handle())?;
+            // Corrupt data files fall back to defaults instead of keeping the app from starting
+            let (mut registry, registry_error) = VaultRegistry::load(data_dir.clone());
+            let (session, session_error) = SessionState::load(&data_dir);
+            let (settings, settings_error) = SettingsState::load(&data_dir);
+            app.manage(StartupErrors::new([registry_error, session_error, settings_error].into_iter().flatten()));
+            session.restore_vault(&mut registry);
+            app.manage(VaultState::new(registry));
+            app.manage(session);
+            app.manage(settings);
+            app.manage(IndexState::default());
+            let git = GitState::default();
+            git.start_worker(app.handle().clone());
+            app.manage(git);
+            let autosave = AutosaveState::new(app.handle().clone());
+            autosave.start_worker();
+            app.manage(autosave);
+            app.manage(SyncState::default());
+            app.manage(CryptoState::default());
+            app.manage(FontState::default());
+            reminders::start_scheduler(app.handle().clone());
+            Ok(())
+        })
+        // Queued autosaves must not be lost when the window is closed mid-delay
+        .on_window_event(|window, event| {
+            if let WindowEvent::CloseRequested { .. } = event {
+                window.state::<AutosaveState>().flush();
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             select_folder,
             list_memos,
             read_memo,
+            read_memo_range,
             save_memo,
+            queue_save,
+            flush_saves,
             create_memo,
             delete_memo,
             rename_memo,
             pick_font_file,
             install_font,
-            get_installed_font_path,
+            list_installed_fonts,
+            list_system_fonts,
+            export_html,
+            export_pdf,
+            get_settings,
+            update_settings,
+            get_folder_config,
+            get_installed_font,
             delete_installed_font,
+            add_vault,
+            remove_vault,
+            list_vaults,
+            switch_vault,
+            get_vault_settings,
+            update_vault_settings,
+            record_opened,
+            get_recent_memos,
+            restore_session,
+            quick_open,
+            pin_memo,
+            unpin_memo,
+            list_pinned,
+            resolve_memo_id,
+            list_tasks,
+            toggle_task,
+            list_reminders,
+            snooze_reminder,
+            git_status,
+            git_log,
+            git_diff,
+            git_restore,
+            get_sync_config,
+            pick_sync_folder,
+            set_sync_webdav,
+            clear_sync,
+            sync_now,
+            encryption_status,
+            unlock_encryption,
+            lock_encryption,
+            encrypt_memo,
+            decrypt_memo,
+            take_startup_errors,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // Quitting the app (e.g. Cmd+Q on macOS) doesn't go through CloseRequested
+            if let (RunEvent::ExitRequested { .. }, Some(autosave)) = (event, app.try_state::<AutosaveState>()) {
+                autosave.flush();
+            }
+        });
 }
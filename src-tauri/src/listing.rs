@@ -0,0 +1,319 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::commands::{CommandError, MemoMeta};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoSortKey {
+    #[default]
+    Modified,
+    Created,
+    Name,
+    Size,
+}
+
+impl MemoSortKey {
+    /// Names read naturally A→Z, everything else newest/largest first
+    fn default_descending(self) -> bool {
+        self != MemoSortKey::Name
+    }
+}
+
+/// Sorting, paging and optional fields for `list_memos`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ListMemosOptions {
+    pub sort: MemoSortKey,
+    pub descending: Option<bool>,
+    /// Pinned memos before the rest, each group in the sort order
+    pub pinned_first: bool,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Page size; everything is returned when omitted
+    pub limit: Option<usize>,
+    /// Plain-text excerpt with Markdown stripped
+    pub include_preview: bool,
+    /// Word/character, heading and task counts
+    pub include_stats: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MemoPage {
+    pub memos: Vec<MemoMeta>,
+    /// Cursor for the following page, `None` on the last page
+    pub next_cursor: Option<String>,
+    /// Number of memos across all pages
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+enum SortValue {
+    Number(u64),
+    Text(String),
+}
+
+/// Position after the last memo of a page. Keyed by sort value rather than index, so a
+/// memo created or deleted between requests doesn't shift later pages.
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    sort: MemoSortKey,
+    descending: bool,
+    pinned_first: bool,
+    pinned: bool,
+    value: SortValue,
+    path: String,
+}
+
+/// Folds a character for name collation: case-insensitive, full-width ASCII as half-width
+/// and katakana as hiragana, so Japanese names sort in kana order regardless of script
+fn collation_fold(c: char) -> char {
+    let c = match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    };
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Splits a name into runs of digits and non-digits
+fn chunks(name: &str) -> Vec<(bool, String)> {
+    let mut chunks: Vec<(bool, String)> = Vec::new();
+    for c in name.chars().map(collation_fold) {
+        let is_digit = c.is_ascii_digit();
+        match chunks.last_mut() {
+            Some((digits, chunk)) if *digits == is_digit => chunk.push(c),
+            _ => chunks.push((is_digit, c.to_string())),
+        }
+    }
+    chunks
+}
+
+/// Natural order: "memo-2" before "memo-10", ignoring case and kana script
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (chunks_a, chunks_b) = (chunks(a), chunks(b));
+    for ((digits_a, a_chunk), (digits_b, b_chunk)) in chunks_a.iter().zip(&chunks_b) {
+        let ordering = if *digits_a && *digits_b {
+            let (a_trimmed, b_trimmed) = (a_chunk.trim_start_matches('0'), b_chunk.trim_start_matches('0'));
+            a_trimmed
+                .len()
+                .cmp(&b_trimmed.len())
+                .then_with(|| a_trimmed.cmp(b_trimmed))
+                .then_with(|| a_chunk.len().cmp(&b_chunk.len()))
+        } else {
+            a_chunk.cmp(b_chunk)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    chunks_a.len().cmp(&chunks_b.len()).then_with(|| a.cmp(b))
+}
+
+fn sort_value(memo: &MemoMeta, sort: MemoSortKey) -> SortValue {
+    match sort {
+        MemoSortKey::Modified => SortValue::Number(memo.modified_at),
+        MemoSortKey::Created => SortValue::Number(memo.created_at),
+        MemoSortKey::Size => SortValue::Number(memo.size),
+        MemoSortKey::Name => SortValue::Text(memo.name.clone()),
+    }
+}
+
+fn compare_values(a: &SortValue, b: &SortValue) -> Ordering {
+    match (a, b) {
+        (SortValue::Number(a), SortValue::Number(b)) => a.cmp(b),
+        (SortValue::Text(a), SortValue::Text(b)) => natural_cmp(a, b),
+        (SortValue::Number(_), SortValue::Text(_)) => Ordering::Less,
+        (SortValue::Text(_), SortValue::Number(_)) => Ordering::Greater,
+    }
+}
+
+/// Total order over (pinned, value, path); the path breaks ties so pages never overlap
+struct Order {
+    sort: MemoSortKey,
+    descending: bool,
+    pinned_first: bool,
+}
+
+impl Order {
+    fn compare(&self, a: (bool, &SortValue, &str), b: (bool, &SortValue, &str)) -> Ordering {
+        let pinned = if self.pinned_first {
+            b.0.cmp(&a.0)
+        } else {
+            Ordering::Equal
+        };
+        let value = compare_values(a.1, b.1).then_with(|| a.2.cmp(b.2));
+        pinned.then(if self.descending { value.reverse() } else { value })
+    }
+
+    fn compare_memos(&self, a: &MemoMeta, b: &MemoMeta) -> Ordering {
+        self.compare(
+            (a.pinned, &sort_value(a, self.sort), &a.path),
+            (b.pinned, &sort_value(b, self.sort), &b.path),
+        )
+    }
+}
+
+fn decode_cursor(cursor: &str, order: &Order) -> Result<Cursor, CommandError> {
+    let cursor: Cursor =
+        serde_json::from_str(cursor).map_err(|e| CommandError::InvalidData(format!("cursor: {}", e)))?;
+    if cursor.sort != order.sort || cursor.descending != order.descending || cursor.pinned_first != order.pinned_first {
+        return Err(CommandError::InvalidData("cursor belongs to a different sort order".to_string()));
+    }
+    Ok(cursor)
+}
+
+/// Sorts a full listing and cuts out the requested page
+pub fn paginate(mut memos: Vec<MemoMeta>, options: &ListMemosOptions) -> Result<MemoPage, CommandError> {
+    let order = Order {
+        sort: options.sort,
+        descending: options.descending.unwrap_or(options.sort.default_descending()),
+        pinned_first: options.pinned_first,
+    };
+    memos.sort_by(|a, b| order.compare_memos(a, b));
+    let total = memos.len();
+
+    let start = match options.cursor.as_deref() {
+        Some(cursor) => {
+            let cursor = decode_cursor(cursor, &order)?;
+            memos.partition_point(|memo| {
+                let value = sort_value(memo, order.sort);
+                order.compare((memo.pinned, &value, &memo.path), (cursor.pinned, &cursor.value, &cursor.path))
+                    != Ordering::Greater
+            })
+        }
+        None => 0,
+    };
+    let end = options.limit.map_or(total, |limit| start.saturating_add(limit.max(1)).min(total));
+
+    let next_cursor = match memos[start..end].last() {
+        Some(last) if end < total => {
+            let cursor = Cursor {
+                sort: order.sort,
+                descending: order.descending,
+                pinned_first: order.pinned_first,
+                pinned: last.pinned,
+                value: sort_value(last, order.sort),
+                path: last.path.clone(),
+            };
+            Some(serde_json::to_string(&cursor).map_err(|e| CommandError::InvalidData(e.to_string()))?)
+        }
+        _ => None,
+    };
+
+    memos.truncate(end);
+    memos.drain(..start);
+    Ok(MemoPage {
+        memos,
+        next_cursor,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::MemoKind;
+
+    fn memo(name: &str, modified_at: u64, pinned: bool) -> MemoMeta {
+        MemoMeta {
+            id: name.to_string(),
+            path: format!("/notes/{}.md", name),
+            name: format!("{}.md", name),
+            kind: MemoKind::Markdown,
+            size: 0,
+            modified_at,
+            created_at: modified_at,
+            pinned,
+            encrypted: false,
+            preview: None,
+            stats: None,
+        }
+    }
+
+    fn names(page: &MemoPage) -> Vec<&str> {
+        page.memos.iter().map(|memo| memo.name.trim_end_matches(".md")).collect()
+    }
+
+    fn options(limit: usize, cursor: Option<String>, pinned_first: bool) -> ListMemosOptions {
+        ListMemosOptions {
+            limit: Some(limit),
+            cursor,
+            pinned_first,
+            ..Default::default()
+        }
+    }
+
+    fn folder() -> Vec<MemoMeta> {
+        vec![
+            memo("a", 10, false),
+            memo("b", 50, false),
+            memo("c", 30, true),
+            memo("d", 40, false),
+            memo("e", 20, true),
+        ]
+    }
+
+    #[test]
+    fn the_last_page_has_no_cursor() {
+        let first = paginate(folder(), &options(3, None, false)).unwrap();
+        assert_eq!(names(&first), ["b", "d", "c"]);
+        let last = paginate(folder(), &options(3, first.next_cursor, false)).unwrap();
+        assert_eq!(names(&last), ["e", "a"]);
+        assert_eq!(last.next_cursor, None);
+        assert_eq!(last.total, 5);
+
+        let exact = paginate(folder(), &options(5, None, false)).unwrap();
+        assert_eq!(exact.memos.len(), 5);
+        assert_eq!(exact.next_cursor, None);
+    }
+
+    #[test]
+    fn a_cursor_after_the_last_memo_gives_an_empty_page() {
+        let first = paginate(folder(), &options(4, None, false)).unwrap();
+        // The memo after the cursor was deleted, leaving the cursor past the end
+        let mut memos = folder();
+        memos.retain(|memo| memo.name != "a.md");
+        let page = paginate(memos, &options(4, first.next_cursor, false)).unwrap();
+        assert!(page.memos.is_empty());
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(page.total, 4);
+    }
+
+    #[test]
+    fn a_cursor_for_a_deleted_memo_continues_after_its_position() {
+        let first = paginate(folder(), &options(2, None, false)).unwrap();
+        assert_eq!(names(&first), ["b", "d"]);
+        let mut memos = folder();
+        memos.retain(|memo| memo.name != "d.md");
+        memos.push(memo("f", 45, false));
+        let second = paginate(memos, &options(2, first.next_cursor, false)).unwrap();
+        // "f" sorts before the deleted "d", so it belongs to a page that was already read
+        assert_eq!(names(&second), ["c", "e"]);
+    }
+
+    #[test]
+    fn pinned_memos_come_first_across_pages() {
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = paginate(folder(), &options(2, cursor, true)).unwrap();
+            seen.extend(page.memos.iter().map(|memo| (memo.pinned, memo.name.clone())));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        let names: Vec<_> = seen.iter().map(|(_, name)| name.trim_end_matches(".md")).collect();
+        assert_eq!(names, ["c", "e", "b", "d", "a"]);
+        assert!(seen.iter().map(|(pinned, _)| !pinned).is_sorted());
+    }
+
+    #[test]
+    fn a_cursor_from_another_order_is_rejected() {
+        let first = paginate(folder(), &options(2, None, true)).unwrap();
+        let result = paginate(folder(), &options(2, first.next_cursor, false));
+        assert!(matches!(result, Err(CommandError::InvalidData(_))));
+    }
+}
@@ -0,0 +1,481 @@
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use std::collections::BTreeMap;
+use std::io::Write;
+use ttf_parser::{name_id, Face};
+
+use crate::commands::{CommandError, MemoKind};
+use crate::fonts;
+use crate::frontmatter;
+
+/// A4 in points
+const PAGE_WIDTH: f32 = 595.28;
+const PAGE_HEIGHT: f32 = 841.89;
+/// 20 mm
+const MARGIN: f32 = 56.7;
+const BODY_SIZE: f32 = 11.0;
+const CODE_SIZE: f32 = 9.5;
+const LINE_HEIGHT: f32 = 1.6;
+/// Indentation per list or block quote level
+const INDENT: f32 = 18.0;
+const TAB: &str = "    ";
+/// Characters a line may not start with (kinsoku)
+const NO_BREAK_BEFORE: &str = "、。，．・：；？！ー）」』】〕〉》”’ぁぃぅぇぉっゃゅょゎァィゥェォッャュョヮヵヶ々〜)]},.:;!?";
+/// Characters a line may not end with
+const NO_BREAK_AFTER: &str = "（「『【〔〈《“‘([{";
+/// Resource name of the embedded font in page content streams
+const FONT_NAME: Name<'static> = Name(b"F1");
+
+/// A laid-out paragraph, heading, list item or code block
+struct Block {
+    text: String,
+    size: f32,
+    indent: f32,
+    /// Extra space above, in multiples of the font size
+    space_before: f32,
+    /// A horizontal rule instead of text
+    rule: bool,
+}
+
+impl Block {
+    fn text(text: String, size: f32, indent: f32, space_before: f32) -> Self {
+        Block {
+            text,
+            size,
+            indent,
+            space_before,
+            rule: false,
+        }
+    }
+}
+
+fn heading_size(level: HeadingLevel) -> f32 {
+    match level {
+        HeadingLevel::H1 => 20.0,
+        HeadingLevel::H2 => 16.0,
+        HeadingLevel::H3 => 14.0,
+        _ => 12.0,
+    }
+}
+
+/// Turns Markdown into blocks of plain text. Inline markup is dropped; list markers, task
+/// checkboxes and table cell separators are kept as text.
+fn markdown_blocks(markdown: &str) -> Vec<Block> {
+    let options = Options::ENABLE_TASKLISTS
+        | Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES;
+    let mut blocks = Vec::new();
+    let mut text = String::new();
+    let mut size = BODY_SIZE;
+    let mut code = false;
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut quotes = 0;
+    let mut cells = 0;
+
+    // List items start at the indent of the text around the list; nested lists go one step in
+    let indent = |lists: &Vec<Option<u64>>, quotes: usize| (lists.len().saturating_sub(1) + quotes) as f32 * INDENT;
+    let flush = |blocks: &mut Vec<Block>, text: &mut String, size: f32, indent: f32| {
+        if !text.trim().is_empty() {
+            let space_before = if size > BODY_SIZE { 0.8 } else { 0.4 };
+            blocks.push(Block::text(text.trim_end().to_string(), size, indent, space_before));
+        }
+        text.clear();
+    };
+
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => size = heading_size(level),
+            Event::Start(Tag::CodeBlock(_)) => {
+                code = true;
+                size = CODE_SIZE;
+            }
+            Event::Start(Tag::List(start)) => {
+                flush(&mut blocks, &mut text, size, indent(&lists, quotes));
+                lists.push(start);
+            }
+            Event::Start(Tag::Item) => {
+                flush(&mut blocks, &mut text, size, indent(&lists, quotes));
+                match lists.last_mut() {
+                    Some(Some(number)) => {
+                        text.push_str(&format!("{}. ", number));
+                        *number += 1;
+                    }
+                    _ => text.push_str("• "),
+                }
+            }
+            Event::Start(Tag::BlockQuote(_)) => quotes += 1,
+            Event::Start(Tag::TableCell) => {
+                if cells > 0 {
+                    text.push_str(" | ");
+                }
+                cells += 1;
+            }
+            Event::End(TagEnd::List(_)) => {
+                flush(&mut blocks, &mut text, size, indent(&lists, quotes));
+                lists.pop();
+            }
+            Event::End(TagEnd::BlockQuote(_)) => quotes -= 1,
+            Event::End(TagEnd::Paragraph | TagEnd::Item | TagEnd::Heading(_) | TagEnd::CodeBlock) => {
+                flush(&mut blocks, &mut text, size, indent(&lists, quotes));
+                size = BODY_SIZE;
+                code = false;
+            }
+            Event::End(TagEnd::TableHead | TagEnd::TableRow) => {
+                flush(&mut blocks, &mut text, size, indent(&lists, quotes));
+                cells = 0;
+            }
+            Event::TaskListMarker(checked) => text.push_str(if checked { "[x] " } else { "[ ] " }),
+            Event::Text(chunk) if code => text.push_str(&chunk.replace('\t', TAB)),
+            Event::Text(chunk) | Event::Code(chunk) => text.push_str(&chunk),
+            Event::FootnoteReference(label) => text.push_str(&format!("[{}]", label)),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak => text.push('\n'),
+            Event::Rule => {
+                flush(&mut blocks, &mut text, size, indent(&lists, quotes));
+                blocks.push(Block {
+                    rule: true,
+                    ..Block::text(String::new(), BODY_SIZE, 0.0, 0.4)
+                });
+            }
+            _ => {}
+        }
+    }
+    flush(&mut blocks, &mut text, size, indent(&lists, quotes));
+    blocks
+}
+
+/// Blocks of a plain text memo: one per paragraph, keeping the line breaks
+fn text_blocks(content: &str) -> Vec<Block> {
+    content
+        .replace("\r\n", "\n")
+        .split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(|paragraph| Block::text(paragraph.replace('\t', TAB), BODY_SIZE, 0.0, 0.6))
+        .collect()
+}
+
+/// Japanese and other full-width text, which may be broken between any two characters
+fn is_wide(c: char) -> bool {
+    matches!(c, '\u{2E80}'..='\u{9FFF}' | '\u{AC00}'..='\u{D7AF}' | '\u{F900}'..='\u{FAFF}' | '\u{FF00}'..='\u{FFEF}')
+}
+
+fn can_break(before: char, after: char) -> bool {
+    if NO_BREAK_BEFORE.contains(after) || NO_BREAK_AFTER.contains(before) {
+        return false;
+    }
+    before == ' ' || is_wide(before) || is_wide(after)
+}
+
+/// Greedy line breaking: after spaces and around full-width characters, or anywhere when a
+/// word is wider than the line
+fn wrap(line: &str, width: f32, advance: impl Fn(char) -> f32) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut lines = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let (mut end, mut x, mut last_break) = (start, 0.0, None);
+        while end < chars.len() {
+            x += advance(chars[end]);
+            if x > width && end > start {
+                break;
+            }
+            end += 1;
+            if end < chars.len() && can_break(chars[end - 1], chars[end]) {
+                last_break = Some(end);
+            }
+        }
+        if end < chars.len() {
+            end = last_break.unwrap_or(end);
+        }
+        lines.push(chars[start..end].iter().collect::<String>().trim_end().to_string());
+        start = end;
+        while chars.get(start) == Some(&' ') {
+            start += 1;
+        }
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    // Writing to a Vec can't fail
+    let _ = encoder.write_all(data);
+    encoder.finish().unwrap_or_default()
+}
+
+/// PDF names only allow printable ASCII without delimiters
+fn postscript_name(face: &Face<'_>) -> String {
+    let name: String = face
+        .names()
+        .into_iter()
+        .filter(|name| name.name_id == name_id::POST_SCRIPT_NAME)
+        .find_map(|name| name.to_string())
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    if name.is_empty() {
+        "Font".to_string()
+    } else {
+        name
+    }
+}
+
+/// Positions of the text lines of one page
+struct Page {
+    lines: Vec<(f32, f32, f32, String)>,
+    rules: Vec<f32>,
+}
+
+/// Splits the blocks into lines and pages
+fn paginate(blocks: &[Block], advance: impl Fn(char, f32) -> f32) -> Vec<Page> {
+    let width = PAGE_WIDTH - 2.0 * MARGIN;
+    let top = PAGE_HEIGHT - MARGIN;
+    let mut pages = vec![Page { lines: Vec::new(), rules: Vec::new() }];
+    let mut y = top;
+    for block in blocks {
+        if y < top {
+            y -= block.space_before * block.size;
+        }
+        if block.rule {
+            if y - block.size < MARGIN {
+                pages.push(Page { lines: Vec::new(), rules: Vec::new() });
+                y = top;
+            }
+            y -= block.size / 2.0;
+            if let Some(page) = pages.last_mut() {
+                page.rules.push(y);
+            }
+            y -= block.size / 2.0;
+            continue;
+        }
+        let line_height = block.size * LINE_HEIGHT;
+        for text_line in block.text.split('\n') {
+            for line in wrap(text_line, width - block.indent, |c| advance(c, block.size)) {
+                if y - line_height < MARGIN {
+                    pages.push(Page { lines: Vec::new(), rules: Vec::new() });
+                    y = top;
+                }
+                let baseline = y - block.size * 1.2;
+                if let Some(page) = pages.last_mut() {
+                    page.lines.push((MARGIN + block.indent, baseline, block.size, line));
+                }
+                y -= line_height;
+            }
+        }
+    }
+    pages
+}
+
+/// Lays out a memo on A4 pages in the given font (a TrueType/OpenType file or collection and
+/// the index of the face to use). Markdown is set as headings, paragraphs, lists and code
+/// blocks; plain text keeps its lines. The font is embedded, subset to the characters used.
+pub fn render_pdf(content: &str, kind: MemoKind, name: &str, font: &[u8], index: u32) -> Result<Vec<u8>, CommandError> {
+    let (title, blocks) = match kind {
+        MemoKind::Text => (name.to_string(), text_blocks(content)),
+        MemoKind::Markdown | MemoKind::Mdx => {
+            let front_matter = frontmatter::parse(content);
+            let title = front_matter.title().unwrap_or(name).to_string();
+            (title, markdown_blocks(&content[front_matter.body_offset..]))
+        }
+    };
+    let used: String = blocks.iter().map(|block| block.text.as_str()).collect();
+    let subset = fonts::subset_sfnt(font, index, &used)?;
+    let face = Face::parse(&subset, 0).map_err(|_| CommandError::UnsupportedFontFormat("could not subset font".to_string()))?;
+    let scale = 1000.0 / f32::from(face.units_per_em());
+    let glyph = |c: char| face.glyph_index(c).map_or(0, |glyph| glyph.0);
+    let advance = |c: char, size: f32| {
+        let glyph = face.glyph_index(c).unwrap_or_default();
+        f32::from(face.glyph_hor_advance(glyph).unwrap_or(0)) * scale * size / 1000.0
+    };
+    let pages = paginate(&blocks, advance);
+
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let type0_id = Ref::new(3);
+    let cid_font_id = Ref::new(4);
+    let descriptor_id = Ref::new(5);
+    let font_file_id = Ref::new(6);
+    let cmap_id = Ref::new(7);
+    let info_id = Ref::new(8);
+    let page_ids: Vec<(Ref, Ref)> = (0..pages.len() as i32)
+        .map(|i| (Ref::new(9 + 2 * i), Ref::new(10 + 2 * i)))
+        .collect();
+
+    let cff = subset.starts_with(b"OTTO");
+    let mut pdf = Pdf::new();
+    // Embedded OpenType (CFF) fonts need PDF 1.6
+    pdf.set_version(1, if cff { 6 } else { 4 });
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().map(|(page_id, _)| *page_id))
+        .count(pages.len() as i32);
+    pdf.document_info(info_id).title(TextStr(&title));
+
+    let base_font = format!("MEMOAA+{}", postscript_name(&face));
+    let system_info = SystemInfo {
+        registry: Str(b"Adobe"),
+        ordering: Str(b"Identity"),
+        supplement: 0,
+    };
+    pdf.type0_font(type0_id)
+        .base_font(Name(base_font.as_bytes()))
+        .encoding_predefined(Name(b"Identity-H"))
+        .descendant_font(cid_font_id)
+        .to_unicode(cmap_id);
+    let mut cid_font = pdf.cid_font(cid_font_id);
+    cid_font
+        .subtype(if cff { CidFontType::Type0 } else { CidFontType::Type2 })
+        .base_font(Name(base_font.as_bytes()))
+        .system_info(system_info)
+        .font_descriptor(descriptor_id)
+        .default_width(0.0);
+    if !cff {
+        cid_font.cid_to_gid_map_predefined(Name(b"Identity"));
+    }
+    cid_font.widths().consecutive(
+        0,
+        (0..face.number_of_glyphs())
+            .map(|glyph| f32::from(face.glyph_hor_advance(ttf_parser::GlyphId(glyph)).unwrap_or(0)) * scale),
+    );
+    cid_font.finish();
+
+    let bbox = face.global_bounding_box();
+    let mut descriptor = pdf.font_descriptor(descriptor_id);
+    descriptor
+        .name(Name(base_font.as_bytes()))
+        .flags(FontFlags::NON_SYMBOLIC)
+        .bbox(Rect::new(
+            f32::from(bbox.x_min) * scale,
+            f32::from(bbox.y_min) * scale,
+            f32::from(bbox.x_max) * scale,
+            f32::from(bbox.y_max) * scale,
+        ))
+        .italic_angle(face.italic_angle())
+        .ascent(f32::from(face.ascender()) * scale)
+        .descent(f32::from(face.descender()) * scale)
+        .cap_height(f32::from(face.capital_height().unwrap_or(face.ascender())) * scale)
+        .stem_v(80.0);
+    if cff {
+        descriptor.font_file3(font_file_id);
+    } else {
+        descriptor.font_file2(font_file_id);
+    }
+    descriptor.finish();
+
+    let compressed_font = compress(&subset);
+    let mut font_file = pdf.stream(font_file_id, &compressed_font);
+    font_file.filter(Filter::FlateDecode);
+    if cff {
+        font_file.pair(Name(b"Subtype"), Name(b"OpenType"));
+    } else {
+        font_file.pair(Name(b"Length1"), subset.len() as i32);
+    }
+    font_file.finish();
+
+    let mut to_unicode: BTreeMap<u16, char> = BTreeMap::new();
+    for c in used.chars() {
+        if let Some(glyph) = face.glyph_index(c) {
+            to_unicode.entry(glyph.0).or_insert(c);
+        }
+    }
+    let mut cmap = UnicodeCmap::new(Name(b"Custom"), system_info);
+    for (glyph, c) in to_unicode {
+        cmap.pair(glyph, c);
+    }
+    pdf.cmap(cmap_id, cmap.finish().as_slice());
+
+    for (page, (page_id, content_id)) in pages.iter().zip(&page_ids) {
+        let mut content = Content::new();
+        for (x, y, size, line) in &page.lines {
+            let glyphs: Vec<u8> = line.chars().flat_map(|c| glyph(c).to_be_bytes()).collect();
+            content.begin_text();
+            content.set_font(FONT_NAME, *size);
+            content.next_line(*x, *y);
+            content.show(Str(&glyphs));
+            content.end_text();
+        }
+        for y in &page.rules {
+            content.set_stroke_gray(0.7);
+            content.set_line_width(0.5);
+            content.move_to(MARGIN, *y);
+            content.line_to(PAGE_WIDTH - MARGIN, *y);
+            content.stroke();
+        }
+        let stream = compress(&content.finish());
+        pdf.stream(*content_id, &stream).filter(Filter::FlateDecode);
+
+        let mut pdf_page = pdf.page(*page_id);
+        pdf_page
+            .media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+            .parent(page_tree_id)
+            .contents(*content_id);
+        pdf_page.resources().fonts().pair(FONT_NAME, type0_id);
+        pdf_page.finish();
+    }
+    Ok(pdf.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fonts::tests::truetype_fixture;
+    use lopdf::{Document, Object};
+    use std::collections::BTreeSet;
+
+    fn stream_content(document: &Document, object: &Object) -> Vec<u8> {
+        let stream = document.get_object(object.as_reference().unwrap()).unwrap().as_stream().unwrap();
+        // Fails for streams without a filter, such as the ToUnicode CMap
+        stream.decompressed_content().unwrap_or_else(|_| stream.content.clone())
+    }
+
+    #[test]
+    fn renders_mixed_text_with_a_subset_font_and_to_unicode_map() {
+        let memo = "# メモ\n\nHello 日本語 text\n";
+        let pdf = render_pdf(memo, MemoKind::Markdown, "memo", &truetype_fixture("Hello textメモ日本語 XYZ漢"), 0).unwrap();
+
+        let document = Document::load_mem(&pdf).expect("output parses");
+        assert_eq!(document.get_pages().len(), 1);
+        let type0 = document
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .find(|dict| dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type0".as_slice()))
+            .expect("Type0 font");
+        assert_eq!(type0.get(b"Encoding").unwrap().as_name().unwrap(), b"Identity-H");
+
+        // The embedded font holds exactly the characters used, plus .notdef
+        let descendant = type0.get(b"DescendantFonts").unwrap().as_array().unwrap()[0].as_reference().unwrap();
+        let descriptor = document.get_dictionary(descendant).unwrap().get(b"FontDescriptor").unwrap().as_reference().unwrap();
+        let font_file = document.get_dictionary(descriptor).unwrap().get(b"FontFile2").unwrap().clone();
+        let subset = stream_content(&document, &font_file);
+        let face = Face::parse(&subset, 0).expect("subset parses");
+        let used: BTreeSet<char> = "メモHello 日本語 text".chars().collect();
+        assert_eq!(usize::from(face.number_of_glyphs()), used.len() + 1);
+        assert!(used.iter().all(|&c| face.glyph_index(c).is_some()));
+        assert!("XYZ漢".chars().all(|c| face.glyph_index(c).is_none()));
+
+        // ToUnicode maps each of those glyphs back to its character: `<glyph> <UTF-16>` pairs
+        let cmap = String::from_utf8(stream_content(&document, type0.get(b"ToUnicode").unwrap())).unwrap();
+        let mapped: BTreeSet<char> = cmap
+            .split("beginbfchar")
+            .skip(1)
+            .flat_map(|section| section.split("endbfchar").next().unwrap().lines())
+            .filter_map(|line| {
+                let (glyph, unicode) = line.strip_prefix('<')?.split_once("> <")?;
+                let glyph = u16::from_str_radix(glyph, 16).ok()?;
+                let c = char::from_u32(u32::from_str_radix(unicode.strip_suffix('>')?, 16).ok()?)?;
+                assert_eq!(face.glyph_index(c).map(|id| id.0), Some(glyph), "{:?}", c);
+                Some(c)
+            })
+            .collect();
+        assert_eq!(mapped, used);
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tauri::State;
+
+use crate::commands::{build_memo_meta, memo_kind, validate_memo_path, CommandError, MemoMeta};
+use crate::ids;
+use crate::storage::{now_millis, read_json, write_json};
+use crate::vault::{VaultRegistry, VaultState};
+
+/// Pins live in the vault's backend data directory, not in the memos' front matter
+const PINS_FILE: &str = "pins.json";
+
+/// A pin is keyed by the memo's stable ID (see `ids.rs`), so it survives renames made
+/// outside the app
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PinnedEntry {
+    #[serde(default)]
+    pub id: String,
+    /// Older versions keyed pins by path; only read to migrate them
+    #[serde(default, skip_serializing)]
+    pub path: Option<String>,
+    pub pinned_at: u64,
+}
+
+fn pins_file(registry: &VaultRegistry) -> Result<PathBuf, CommandError> {
+    Ok(registry.active_vault_dir()?.join(PINS_FILE))
+}
+
+/// Looks up the ID of a memo, assigning one if no listing has seen it yet
+fn memo_id(registry: &VaultRegistry, path: &str) -> Option<String> {
+    let memo_path = PathBuf::from(path);
+    let kind = memo_kind(&memo_path).ok()?;
+    if !memo_path.is_file() {
+        return None;
+    }
+    let mut meta = build_memo_meta(&memo_path, kind);
+    meta.path = path.to_string();
+    ids::assign_id(registry, &mut meta).ok()?;
+    Some(meta.id)
+}
+
+/// Reads the pins, rewriting path-keyed ones from older versions under their memo's ID.
+/// Pins of memos that are gone by then are dropped.
+fn load_pins(registry: &VaultRegistry) -> Result<Vec<PinnedEntry>, CommandError> {
+    let mut pins: Vec<PinnedEntry> = read_json(&pins_file(registry)?)?;
+    if pins.iter().all(|entry| entry.path.is_none()) {
+        return Ok(pins);
+    }
+    let mut migrated: Vec<PinnedEntry> = Vec::with_capacity(pins.len());
+    for mut entry in pins.drain(..) {
+        if let Some(path) = entry.path.take() {
+            match memo_id(registry, &path) {
+                Some(id) => entry.id = id,
+                None => continue,
+            }
+        }
+        if !migrated.iter().any(|pinned| pinned.id == entry.id) {
+            migrated.push(entry);
+        }
+    }
+    write_json(&pins_file(registry)?, &migrated)?;
+    Ok(migrated)
+}
+
+/// IDs of the memos pinned in the working folder
+pub fn pinned_ids(registry: &VaultRegistry) -> Result<HashSet<String>, CommandError> {
+    Ok(load_pins(registry)?.into_iter().map(|entry| entry.id).collect())
+}
+
+/// Drops the pin of a deleted memo
+pub fn forget_pin(registry: &VaultRegistry, id: &str) -> Result<(), CommandError> {
+    let mut pins = load_pins(registry)?;
+    let before = pins.len();
+    pins.retain(|entry| entry.id != id);
+    if pins.len() == before {
+        return Ok(());
+    }
+    write_json(&pins_file(registry)?, &pins)
+}
+
+#[tauri::command]
+pub fn pin_memo(state: State<'_, VaultState>, file_path: String) -> Result<MemoMeta, CommandError> {
+    let registry = state.lock();
+    let folder = registry.active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, kind) = validate_memo_path(&folder, &file_path)?;
+
+    let mut meta = build_memo_meta(&path, kind);
+    meta.path = file_path;
+    ids::assign_id(&registry, &mut meta)?;
+
+    let mut pins = load_pins(&registry)?;
+    if !pins.iter().any(|entry| entry.id == meta.id) {
+        pins.push(PinnedEntry {
+            id: meta.id.clone(),
+            path: None,
+            pinned_at: now_millis(),
+        });
+        write_json(&pins_file(&registry)?, &pins)?;
+    }
+    meta.pinned = true;
+    Ok(meta)
+}
+
+/// Unpins a memo. Memos without an ID were never pinned, so they are ignored.
+#[tauri::command]
+pub fn unpin_memo(state: State<'_, VaultState>, file_path: String) -> Result<(), CommandError> {
+    let registry = state.lock();
+    let id = ids::known_paths(&registry)?
+        .into_iter()
+        .find_map(|(id, path)| (path == file_path).then_some(id));
+    match id {
+        Some(id) => forget_pin(&registry, &id),
+        None => Ok(()),
+    }
+}
+
+/// Returns pinned memos in the order they were pinned, skipping files that no longer exist.
+/// A memo renamed outside the app shows up again once a listing has picked up the rename.
+#[tauri::command]
+pub fn list_pinned(state: State<'_, VaultState>) -> Result<Vec<MemoMeta>, CommandError> {
+    let registry = state.lock();
+    let mut paths = ids::known_paths(&registry)?;
+    let memos: Vec<MemoMeta> = load_pins(&registry)?
+        .into_iter()
+        .filter_map(|entry| {
+            let path = PathBuf::from(paths.remove(&entry.id)?);
+            let kind = memo_kind(&path).ok()?;
+            path.is_file().then(|| {
+                let mut meta = build_memo_meta(&path, kind);
+                meta.id = entry.id;
+                meta.pinned = true;
+                meta
+            })
+        })
+        .collect();
+    Ok(memos)
+}
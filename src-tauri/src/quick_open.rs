@@ -0,0 +1,327 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::{scan_memos, CommandError, MemoMeta};
+use crate::ids;
+use crate::index::{self, IndexState};
+use crate::session;
+use crate::vault::VaultState;
+
+const DEFAULT_LIMIT: usize = 50;
+
+// Scoring weights. A matched character is worth SCORE_MATCH; bonuses reward matches that
+// start a path segment or word and runs of consecutive characters, gaps cost a little.
+const SCORE_MATCH: i64 = 16;
+const BONUS_SEGMENT_START: i64 = 24;
+const BONUS_WORD_START: i64 = 16;
+const BONUS_CAMEL_CASE: i64 = 12;
+const BONUS_CONSECUTIVE: i64 = 12;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+const MAX_LEADING_PENALTY: i64 = 8;
+/// Boost for the most recently opened memo, decreasing linearly down the recent list
+const MAX_RECENCY_BOOST: i64 = 40;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchField {
+    Name,
+    Title,
+    Alias,
+    Path,
+}
+
+impl MatchField {
+    /// Prefer a hit on the file name over the same hit in secondary fields
+    fn penalty(self) -> i64 {
+        match self {
+            MatchField::Name => 0,
+            MatchField::Title => 2,
+            MatchField::Alias => 4,
+            MatchField::Path => 8,
+        }
+    }
+}
+
+/// Highlight range in UTF-16 code units, so it can be used directly with `String.prototype.slice`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct MatchRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuickOpenMatch {
+    pub memo: MemoMeta,
+    pub score: i64,
+    pub field: MatchField,
+    /// The text that matched (name, title, alias or relative path)
+    pub text: String,
+    pub ranges: Vec<MatchRange>,
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn is_word_separator(c: char) -> bool {
+    matches!(c, ' ' | '-' | '_' | '.' | '　')
+}
+
+fn char_bonus(prev: Option<char>, current: char) -> i64 {
+    match prev {
+        None | Some('/') | Some('\\') => BONUS_SEGMENT_START,
+        Some(p) if is_word_separator(p) => BONUS_WORD_START,
+        Some(p) if p.is_lowercase() && current.is_uppercase() => BONUS_CAMEL_CASE,
+        Some(p) if !p.is_ascii_digit() && current.is_ascii_digit() => BONUS_CAMEL_CASE,
+        _ => 0,
+    }
+}
+
+/// Scores `candidate` against an already case-folded query. Returns the best score and
+/// the matched character positions, or `None` if the query is not a subsequence.
+pub fn fuzzy_match(query: &[char], candidate: &str) -> Option<(i64, Vec<usize>)> {
+    const NONE: i64 = i64::MIN / 2;
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let folded: Vec<char> = chars.iter().copied().map(fold).collect();
+    let (m, n) = (query.len(), chars.len());
+    if m == 0 || m > n {
+        return None;
+    }
+
+    // Cheap subsequence check before running the full DP
+    let mut remaining = query.iter().peekable();
+    for c in &folded {
+        if remaining.peek() == Some(&c) {
+            remaining.next();
+        }
+    }
+    if remaining.peek().is_some() {
+        return None;
+    }
+
+    let bonus: Vec<i64> = (0..n)
+        .map(|j| char_bonus(j.checked_sub(1).map(|k| chars[k]), chars[j]))
+        .collect();
+
+    // score[i * n + j]: best score with query[i] matched at candidate[j]; from: previous match position
+    let mut score = vec![NONE; m * n];
+    let mut from = vec![usize::MAX; m * n];
+
+    for (i, q) in query.iter().enumerate() {
+        // Best predecessor at least two positions back, already charged for the gap
+        let mut gapped = NONE;
+        let mut gapped_pos = usize::MAX;
+
+        for j in i..n {
+            if i > 0 && j >= 2 {
+                let candidate = score[(i - 1) * n + j - 2] - PENALTY_GAP_START;
+                gapped -= PENALTY_GAP_EXTENSION;
+                if candidate > gapped {
+                    gapped = candidate;
+                    gapped_pos = j - 2;
+                }
+            }
+            if folded[j] != *q {
+                continue;
+            }
+
+            let base = SCORE_MATCH + bonus[j];
+            let idx = i * n + j;
+            if i == 0 {
+                score[idx] = base - (j as i64).min(MAX_LEADING_PENALTY);
+                continue;
+            }
+
+            let consecutive = score[(i - 1) * n + j - 1];
+            if consecutive > NONE && consecutive + BONUS_CONSECUTIVE >= gapped {
+                score[idx] = consecutive + base + BONUS_CONSECUTIVE;
+                from[idx] = j - 1;
+            } else if gapped > NONE {
+                score[idx] = gapped + base;
+                from[idx] = gapped_pos;
+            }
+        }
+    }
+
+    let last_row = (m - 1) * n;
+    let (mut j, best) = (0..n)
+        .map(|j| (j, score[last_row + j]))
+        .max_by_key(|(_, s)| *s)
+        .filter(|(_, s)| *s > NONE)?;
+
+    let mut positions = vec![0; m];
+    for i in (0..m).rev() {
+        positions[i] = j;
+        j = from[i * n + j];
+    }
+    Some((best, positions))
+}
+
+/// Converts matched character positions into merged UTF-16 ranges
+fn to_ranges(text: &str, positions: &[usize]) -> Vec<MatchRange> {
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+    let mut offset = 0;
+    for c in text.chars() {
+        offsets.push(offset);
+        offset += c.len_utf16();
+    }
+    offsets.push(offset);
+
+    let mut ranges: Vec<MatchRange> = Vec::new();
+    for &pos in positions {
+        let (start, end) = (offsets[pos], offsets[pos + 1]);
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(MatchRange { start, end }),
+        }
+    }
+    ranges
+}
+
+fn relative_path(folder: &Path, path: &str) -> String {
+    Path::new(path)
+        .strip_prefix(folder)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// Fuzzy-matches memo names, titles, aliases and relative paths of the working folder.
+/// An empty query lists recently opened memos first, then the rest by modification time.
+#[tauri::command]
+pub fn quick_open(
+    vaults: State<'_, VaultState>,
+    index: State<'_, IndexState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<QuickOpenMatch>, CommandError> {
+    let (vault, folder, settings) = {
+        let registry = vaults.lock();
+        (registry.active_vault()?.id.clone(), registry.active_folder()?, registry.active_settings()?)
+    };
+    // Runs on every keystroke, so the folder is only rescanned once the cached listing is stale
+    let modified = index::folder_modified(&folder);
+    let memos = match index.with(&vault, |index| index.listing(&folder, &settings.extensions, modified)) {
+        Some(memos) => memos,
+        None => {
+            let mut memos = scan_memos(&folder, &settings.extensions)?;
+            ids::sync_ids(&vaults.lock(), &mut memos)?;
+            index.with(&vault, |index| index.set_listing(&folder, &settings.extensions, modified, &memos));
+            memos
+        }
+    };
+    // Syncing IDs may move recent entries of memos renamed outside the app, so read them afterwards
+    let recent = session::recent_paths(&vaults.lock())?;
+    let indexed = index.with(&vault, |index| index.refresh(&memos));
+
+    let recency: HashMap<&str, i64> = recent
+        .iter()
+        .enumerate()
+        .map(|(rank, path)| {
+            let boost = MAX_RECENCY_BOOST * (recent.len() - rank) as i64 / recent.len() as i64;
+            (path.as_str(), boost)
+        })
+        .collect();
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).map(fold).collect();
+
+    let mut matches: Vec<QuickOpenMatch> = Vec::new();
+    for (memo, entry) in memos.into_iter().zip(indexed) {
+        let boost = recency.get(memo.path.as_str()).copied().unwrap_or(0);
+
+        if query.is_empty() {
+            matches.push(QuickOpenMatch {
+                text: memo.name.clone(),
+                memo,
+                score: boost,
+                field: MatchField::Name,
+                ranges: Vec::new(),
+            });
+            continue;
+        }
+
+        let mut fields = vec![(MatchField::Name, memo.name.clone())];
+        if let Some(title) = entry.title {
+            fields.push((MatchField::Title, title));
+        }
+        fields.extend(entry.aliases.into_iter().map(|alias| (MatchField::Alias, alias)));
+        fields.push((MatchField::Path, relative_path(&folder, &memo.path)));
+
+        let best = fields
+            .into_iter()
+            .filter_map(|(field, text)| {
+                let (score, positions) = fuzzy_match(&query, &text)?;
+                Some((score - field.penalty(), field, text, positions))
+            })
+            .max_by_key(|(score, ..)| *score);
+
+        if let Some((score, field, text, positions)) = best {
+            matches.push(QuickOpenMatch {
+                ranges: to_ranges(&text, &positions),
+                memo,
+                score: score + boost,
+                field,
+                text,
+            });
+        }
+    }
+
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| b.memo.modified_at.cmp(&a.memo.modified_at))
+    });
+    matches.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(text: &str) -> Vec<char> {
+        text.chars().map(fold).collect()
+    }
+
+    fn positions(q: &str, candidate: &str) -> Option<Vec<usize>> {
+        fuzzy_match(&query(q), candidate).map(|(_, positions)| positions)
+    }
+
+    fn score(q: &str, candidate: &str) -> i64 {
+        fuzzy_match(&query(q), candidate).unwrap().0
+    }
+
+    #[test]
+    fn only_subsequences_match() {
+        assert_eq!(positions("", "memo"), None);
+        assert_eq!(positions("memos", "memo"), None);
+        assert_eq!(positions("om", "memo"), None);
+        assert_eq!(positions("MEMO", "memo"), Some(vec![0, 1, 2, 3]));
+        assert_eq!(positions("めも", "メモとめも"), Some(vec![3, 4]));
+    }
+
+    #[test]
+    fn matches_prefer_word_starts_and_runs() {
+        assert_eq!(positions("mn", "meeting notes"), Some(vec![0, 8]));
+        assert_eq!(positions("qo", "QuickOpen"), Some(vec![0, 5]));
+        assert_eq!(positions("note", "a note and notes"), Some(vec![2, 3, 4, 5]));
+        assert_eq!(positions("2026", "log-2026-10"), Some(vec![4, 5, 6, 7]));
+        assert!(score("memo", "memo.md") > score("memo", "notes/memo.md"));
+        assert!(score("memo", "notes/memo.md") > score("memo", "some memo.md"));
+        assert!(score("memo", "some memo.md") > score("memo", "mxexmxo.md"));
+    }
+
+    #[test]
+    fn ranges_merge_adjacent_positions_in_utf16_units() {
+        assert_eq!(
+            to_ranges("notes", &[0, 1, 2, 4]),
+            [MatchRange { start: 0, end: 3 }, MatchRange { start: 4, end: 5 }]
+        );
+        // The emoji takes two UTF-16 units
+        assert_eq!(to_ranges("😀メモ", &[1, 2]), [MatchRange { start: 2, end: 4 }]);
+        assert_eq!(to_ranges("a😀b", &[0, 1, 2]), [MatchRange { start: 0, end: 4 }]);
+        assert!(to_ranges("memo", &[]).is_empty());
+    }
+}
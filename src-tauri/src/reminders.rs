@@ -0,0 +1,265 @@
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::commands::{build_memo_meta, scan_memos, CommandError, MemoMeta};
+use crate::ids;
+use crate::index::IndexState;
+use crate::storage::{now_millis, read_json, write_json};
+use crate::vault::{VaultRegistry, VaultState};
+
+/// Fired and snoozed reminders, kept in the vault's backend data directory
+const REMINDERS_FILE: &str = "reminders.json";
+/// Emitted with the reminders that just came due
+pub const REMINDERS_DUE_EVENT: &str = "reminders-due";
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// The scheduler lists the folder again after this long even if it looks unchanged, to pick up
+/// ignore file edits and memos that got their ID from a listing since
+const RESCAN_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Date-only due dates come due at this local time
+const DEFAULT_REMIND_HOUR: u32 = 9;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderSource {
+    Task,
+    FrontMatter,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Reminder {
+    /// Identifies the reminder for `snooze_reminder`; stays the same when the memo is renamed
+    pub key: String,
+    pub memo: MemoMeta,
+    pub source: ReminderSource,
+    /// 1-based line of the task (tasks only)
+    pub line: Option<usize>,
+    /// Task text, or the memo title for front matter due dates
+    pub text: String,
+    /// Unix ms
+    pub due_at: u64,
+    pub snoozed_until: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct ReminderData {
+    /// Key → due time the reminder last fired for, so it fires again if the date changes
+    fired: HashMap<String, u64>,
+    /// Key → Unix ms until which the reminder stays quiet
+    snoozed: HashMap<String, u64>,
+}
+
+/// Parses `2026-10-20`, `2026-10-20 14:30` or `2026-10-20T14:30[:00]` as local time (Unix ms)
+pub fn parse_due_at(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let date_time = ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"]
+        .into_iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            Some(date.and_time(NaiveTime::from_hms_opt(DEFAULT_REMIND_HOUR, 0, 0)?))
+        })?;
+    let local = Local.from_local_datetime(&date_time).earliest()?;
+    u64::try_from(local.timestamp_millis()).ok()
+}
+
+fn reminders_file(registry: &VaultRegistry) -> Result<PathBuf, CommandError> {
+    Ok(registry.active_vault_dir()?.join(REMINDERS_FILE))
+}
+
+/// Collects every open task and front matter `due:` date of the given memos
+fn memo_reminders(index: &IndexState, vault: &str, memos: &[MemoMeta]) -> Vec<Reminder> {
+    let mut reminders = Vec::new();
+    index.with(vault, |index| {
+        let indexed = index.refresh(memos);
+        for (memo, entry) in memos.iter().zip(indexed) {
+            // Found by the scheduler before any listing gave it an ID; picked up once one has
+            if memo.id.is_empty() {
+                continue;
+            }
+            if let Some(due_at) = entry.due.as_deref().and_then(parse_due_at) {
+                reminders.push(Reminder {
+                    key: format!("{}:front_matter", memo.id),
+                    text: entry.title.unwrap_or_else(|| memo.name.clone()),
+                    memo: memo.clone(),
+                    source: ReminderSource::FrontMatter,
+                    line: None,
+                    due_at,
+                    snoozed_until: None,
+                });
+            }
+            for task in index.tasks(memo).into_iter().filter(|task| !task.done) {
+                let Some(due_at) = task.due.as_deref().and_then(parse_due_at) else {
+                    continue;
+                };
+                reminders.push(Reminder {
+                    // The line tells apart tasks with the same text; the text keeps another task
+                    // moving onto this line from inheriting its fired or snoozed state
+                    key: format!("{}:task:{}:{}", memo.id, task.line, task.text),
+                    memo: memo.clone(),
+                    source: ReminderSource::Task,
+                    line: Some(task.line),
+                    text: task.text,
+                    due_at,
+                    snoozed_until: None,
+                });
+            }
+        }
+    });
+
+    reminders
+}
+
+/// Fills in which reminders are snoozed and orders them by due time
+fn apply_snoozes(reminders: &mut [Reminder], data: &ReminderData) {
+    let now = now_millis();
+    for reminder in reminders.iter_mut() {
+        reminder.snoozed_until = data.snoozed.get(&reminder.key).copied().filter(|until| *until > now);
+    }
+    reminders.sort_by_key(|reminder| reminder.due_at);
+}
+
+/// Collects every open task and front matter `due:` date of the working folder
+fn collect_reminders(vaults: &VaultState, index: &IndexState) -> Result<Vec<Reminder>, CommandError> {
+    let (vault, folder, settings, data) = {
+        let registry = vaults.lock();
+        let data: ReminderData = read_json(&reminders_file(&registry)?)?;
+        (registry.active_vault()?.id.clone(), registry.active_folder()?, registry.active_settings()?, data)
+    };
+    let mut memos = scan_memos(&folder, &settings.extensions)?;
+    ids::sync_ids(&vaults.lock(), &mut memos)?;
+
+    let mut reminders = memo_reminders(index, &vault, &memos);
+    apply_snoozes(&mut reminders, &data);
+    Ok(reminders)
+}
+
+/// The working folder's memos as the scheduler last listed them
+struct Scan {
+    vault: String,
+    /// Changes when a memo is created, deleted or renamed
+    folder_modified: Option<SystemTime>,
+    memos: Vec<MemoMeta>,
+    at: Instant,
+}
+
+/// The memos the scheduler checks: the last scan with fresh metadata while the folder itself
+/// is unchanged, a new scan otherwise. Only reads `ids.json`, so it never races the commands'
+/// writes; memo contents come from the index, which rereads only memos whose mtime changed.
+fn scheduler_memos(vaults: &VaultState, last: Option<Scan>) -> Result<Scan, CommandError> {
+    let (vault, folder, settings) = {
+        let registry = vaults.lock();
+        (registry.active_vault()?.id.clone(), registry.active_folder()?, registry.active_settings()?)
+    };
+    let folder_modified = fs::metadata(&folder).and_then(|metadata| metadata.modified()).ok();
+    let last = last.filter(|scan| {
+        scan.vault == vault && scan.folder_modified == folder_modified && scan.at.elapsed() < RESCAN_INTERVAL
+    });
+    let (mut memos, at) = match last {
+        Some(scan) => {
+            // Saves change only size and mtime, which is all the index keys on
+            let memos = scan
+                .memos
+                .into_iter()
+                .map(|memo| build_memo_meta(&PathBuf::from(memo.path), memo.kind))
+                .collect();
+            (memos, scan.at)
+        }
+        None => (scan_memos(&folder, &settings.extensions)?, Instant::now()),
+    };
+    ids::known_ids(&vaults.lock(), &mut memos)?;
+    Ok(Scan { vault, folder_modified, memos, at })
+}
+
+/// Fires the reminders that came due since the last check
+fn check_due(app: &AppHandle, scan: &mut Option<Scan>) -> Result<(), CommandError> {
+    let vaults = app.state::<VaultState>();
+    let current = scheduler_memos(&vaults, scan.take())?;
+    let mut reminders = memo_reminders(&app.state::<IndexState>(), &current.vault, &current.memos);
+    *scan = Some(current);
+
+    let registry = vaults.lock();
+    let file = reminders_file(&registry)?;
+    let mut data: ReminderData = read_json(&file)?;
+    apply_snoozes(&mut reminders, &data);
+    let now = now_millis();
+
+    let due: Vec<Reminder> = reminders
+        .iter()
+        .filter(|reminder| reminder.due_at <= now && reminder.snoozed_until.is_none())
+        .filter(|reminder| data.fired.get(&reminder.key) != Some(&reminder.due_at))
+        .cloned()
+        .collect();
+
+    // Forget reminders whose task was ticked off or whose date was removed
+    let before = (data.fired.len(), data.snoozed.len());
+    data.fired.retain(|key, _| reminders.iter().any(|reminder| &reminder.key == key));
+    data.snoozed
+        .retain(|key, until| *until > now && reminders.iter().any(|reminder| &reminder.key == key));
+    if due.is_empty() && before == (data.fired.len(), data.snoozed.len()) {
+        return Ok(());
+    }
+    for reminder in &due {
+        data.fired.insert(reminder.key.clone(), reminder.due_at);
+        data.snoozed.remove(&reminder.key);
+    }
+    write_json(&file, &data)?;
+    drop(registry);
+
+    if due.is_empty() {
+        return Ok(());
+    }
+    let _ = app.emit(REMINDERS_DUE_EVENT, &due);
+    // One notification per check, so a backlog of overdue tasks doesn't flood the desktop
+    let (title, body) = match due.as_slice() {
+        [reminder] => (reminder.memo.name.clone(), reminder.text.clone()),
+        _ => (
+            format!("{} reminders due", due.len()),
+            due.iter().map(|reminder| reminder.text.as_str()).collect::<Vec<_>>().join("\n"),
+        ),
+    };
+    let _ = app.notification().builder().title(title).body(body).show();
+    Ok(())
+}
+
+/// Starts the background thread checking the working folder for due reminders.
+/// Called once from `run()` at startup.
+pub fn start_scheduler(app: AppHandle) {
+    thread::spawn(move || {
+        let mut scan = None;
+        loop {
+            // No working folder open yet is the normal case, not a failure worth reporting
+            let _ = check_due(&app, &mut scan);
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+/// Lists the due dates of the working folder, overdue and upcoming, ordered by due time
+#[tauri::command]
+pub fn list_reminders(
+    vaults: State<'_, VaultState>,
+    index: State<'_, IndexState>,
+) -> Result<Vec<Reminder>, CommandError> {
+    collect_reminders(&vaults, &index)
+}
+
+/// Silences a reminder for the given number of minutes; it fires again afterwards
+#[tauri::command]
+pub fn snooze_reminder(state: State<'_, VaultState>, key: String, minutes: u32) -> Result<u64, CommandError> {
+    let registry = state.lock();
+    let file = reminders_file(&registry)?;
+    let mut data: ReminderData = read_json(&file)?;
+    let until = now_millis() + u64::from(minutes.max(1)) * 60 * 1000;
+    data.snoozed.insert(key.clone(), until);
+    data.fired.remove(&key);
+    write_json(&file, &data)?;
+    Ok(until)
+}
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::State;
+
+use crate::commands::{build_memo_meta, memo_kind, validate_memo_path, CommandError, MemoMeta};
+use crate::ids;
+use crate::storage::{self, now_millis, read_json, write_json};
+use crate::vault::{Vault, VaultRegistry, VaultState};
+
+const SESSION_FILE: &str = "session.json";
+const RECENT_FILE: &str = "recent.json";
+const MAX_RECENT_MEMOS: usize = 50;
+
+/// Editor position restored together with the last open memo
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct EditorPosition {
+    pub cursor: Option<u32>,
+    pub scroll_top: Option<f64>,
+}
+
+/// The last session, persisted as `session.json` in the app data directory
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Session {
+    pub vault_id: Option<String>,
+    pub memo_path: Option<String>,
+    #[serde(default)]
+    pub position: EditorPosition,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentEntry {
+    pub path: String,
+    pub opened_at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentMemo {
+    pub memo: MemoMeta,
+    pub opened_at: u64,
+}
+
+/// What the frontend needs to reopen the previous session
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RestoredSession {
+    pub vault: Vault,
+    pub memo: Option<MemoMeta>,
+    pub position: EditorPosition,
+}
+
+/// Managed state holding the current session
+pub struct SessionState {
+    file: PathBuf,
+    session: Mutex<Session>,
+}
+
+impl SessionState {
+    /// Reads `session.json`, starting a fresh session if it is corrupt (the error is returned
+    /// for reporting)
+    pub fn load(data_dir: &Path) -> (Self, Option<CommandError>) {
+        let file = data_dir.join(SESSION_FILE);
+        let (session, error) = storage::load_or_default(&file, read_json);
+        let state = SessionState {
+            file,
+            session: Mutex::new(session),
+        };
+        (state, error)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Session> {
+        self.session.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Re-activates the vault of the last session. Called once from `run()` at startup.
+    pub fn restore_vault(&self, registry: &mut VaultRegistry) {
+        let session = self.lock();
+        if let Some(vault_id) = session.vault_id.as_deref() {
+            // A vault that was removed or whose folder disappeared simply isn't restored
+            let _ = registry.activate(vault_id);
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Session)) -> Result<(), CommandError> {
+        let mut session = self.lock();
+        f(&mut session);
+        write_json(&self.file, &*session)
+    }
+}
+
+fn recent_file(registry: &VaultRegistry) -> Result<PathBuf, CommandError> {
+    Ok(registry.active_vault_dir()?.join(RECENT_FILE))
+}
+
+/// Paths of recently opened memos in the working folder, newest first
+pub fn recent_paths(registry: &VaultRegistry) -> Result<Vec<String>, CommandError> {
+    let entries: Vec<RecentEntry> = read_json(&recent_file(registry)?)?;
+    Ok(entries.into_iter().map(|entry| entry.path).collect())
+}
+
+/// Keeps the recent list in step with a rename done through the app
+pub fn rename_recent(registry: &VaultRegistry, old_path: &str, new_path: &str) -> Result<(), CommandError> {
+    let file = recent_file(registry)?;
+    let mut entries: Vec<RecentEntry> = read_json(&file)?;
+    if !entries.iter().any(|entry| entry.path == old_path) {
+        return Ok(());
+    }
+    for entry in entries.iter_mut().filter(|entry| entry.path == old_path) {
+        entry.path = new_path.to_string();
+    }
+    write_json(&file, &entries)
+}
+
+/// Drops a deleted memo from the recent list
+pub fn forget_recent(registry: &VaultRegistry, path: &str) -> Result<(), CommandError> {
+    let file = recent_file(registry)?;
+    let mut entries: Vec<RecentEntry> = read_json(&file)?;
+    let before = entries.len();
+    entries.retain(|entry| entry.path != path);
+    if entries.len() == before {
+        return Ok(());
+    }
+    write_json(&file, &entries)
+}
+
+/// Records that a memo was opened, moving it to the top of the recent list and
+/// remembering it (with the editor position) as the memo to restore on next launch
+#[tauri::command]
+pub fn record_opened(
+    vaults: State<'_, VaultState>,
+    session: State<'_, SessionState>,
+    file_path: String,
+    position: Option<EditorPosition>,
+) -> Result<(), CommandError> {
+    let registry = vaults.lock();
+    let folder = registry.active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    validate_memo_path(&folder, &file_path)?;
+
+    let file = recent_file(&registry)?;
+    let mut entries: Vec<RecentEntry> = read_json(&file)?;
+    entries.retain(|entry| entry.path != file_path);
+    entries.insert(
+        0,
+        RecentEntry {
+            path: file_path.clone(),
+            opened_at: now_millis(),
+        },
+    );
+    entries.truncate(MAX_RECENT_MEMOS);
+    write_json(&file, &entries)?;
+
+    let vault_id = registry.active_vault()?.id.clone();
+    session.update(|session| {
+        session.vault_id = Some(vault_id);
+        session.memo_path = Some(file_path);
+        session.position = position.unwrap_or_default();
+    })
+}
+
+/// Returns recently opened memos of the working folder, newest first, skipping files that are gone
+#[tauri::command]
+pub fn get_recent_memos(vaults: State<'_, VaultState>, limit: Option<usize>) -> Result<Vec<RecentMemo>, CommandError> {
+    let registry = vaults.lock();
+    let entries: Vec<RecentEntry> = read_json(&recent_file(&registry)?)?;
+
+    let mut memos: Vec<RecentMemo> = entries
+        .into_iter()
+        .filter_map(|entry| {
+            let path = PathBuf::from(&entry.path);
+            let kind = memo_kind(&path).ok()?;
+            path.is_file().then(|| RecentMemo {
+                memo: build_memo_meta(&path, kind),
+                opened_at: entry.opened_at,
+            })
+        })
+        .take(limit.unwrap_or(MAX_RECENT_MEMOS))
+        .collect();
+    for recent in memos.iter_mut() {
+        ids::assign_id(&registry, &mut recent.memo)?;
+    }
+    Ok(memos)
+}
+
+/// Returns the working folder and memo that were open when the app last closed.
+/// The vault itself is re-activated at startup, so memo commands work immediately.
+#[tauri::command]
+pub fn restore_session(
+    vaults: State<'_, VaultState>,
+    session: State<'_, SessionState>,
+) -> Result<Option<RestoredSession>, CommandError> {
+    let registry = vaults.lock();
+    let Ok(vault) = registry.active_vault() else {
+        return Ok(None);
+    };
+    let folder = registry.active_folder()?;
+    let session = session.lock().clone();
+
+    // Only restore the memo if it belongs to the active vault and still exists inside it
+    let mut memo = session
+        .memo_path
+        .filter(|_| session.vault_id.as_deref() == Some(vault.id.as_str()))
+        .and_then(|path| validate_memo_path(&folder, &path).ok())
+        .map(|(path, kind)| build_memo_meta(&path, kind));
+    if let Some(memo) = memo.as_mut() {
+        ids::assign_id(&registry, memo)?;
+    }
+    let position = if memo.is_some() {
+        session.position
+    } else {
+        EditorPosition::default()
+    };
+
+    Ok(Some(RestoredSession {
+        vault: vault.clone(),
+        memo,
+        position,
+    }))
+}
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::State;
+
+use crate::commands::CommandError;
+use crate::fonts::{self, FontState};
+use crate::storage::{self, read_json, write_json};
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Version written by this build. Files from older versions are migrated when read.
+pub const SETTINGS_VERSION: u32 = 1;
+const MIN_AUTOSAVE_DELAY_MS: u64 = 200;
+const MAX_AUTOSAVE_DELAY_MS: u64 = 60_000;
+
+/// Font used by the editor (and by exports unless another one is chosen)
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum EditorFont {
+    #[default]
+    Default,
+    /// A font installed with `install_font`
+    File { id: String, label: String, format: String },
+    /// A system font, used by family name
+    System { family: String, label: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    /// Idle time after the last edit before the memo is saved
+    pub delay_ms: u64,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        AutosaveSettings {
+            enabled: false,
+            delay_ms: 1000,
+        }
+    }
+}
+
+/// App-wide settings, persisted as `settings.json` in the app data directory.
+/// Per-vault settings live in `VaultSettings`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AppSettings {
+    pub version: u32,
+    pub editor_font: EditorFont,
+    pub autosave: AutosaveSettings,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            version: SETTINGS_VERSION,
+            editor_font: EditorFont::Default,
+            autosave: AutosaveSettings::default(),
+        }
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> CommandError {
+    CommandError::InvalidSettings(reason.into())
+}
+
+/// Version 0 is the frontend's `memo-settings` localStorage entry (a zustand `persist` blob,
+/// `{"state": {"editorFont": ...}, "version": 0}`). Installed fonts are dropped: they are
+/// tracked in `fonts.json`.
+fn migrate_from_frontend_store(value: Value) -> Value {
+    let state = value.get("state").unwrap_or(&value);
+    json!({
+        "version": 1,
+        "editor_font": state.get("editorFont").cloned().unwrap_or(json!({ "type": "default" })),
+    })
+}
+
+/// Brings a settings document of any earlier version up to `SETTINGS_VERSION`
+fn migrate(mut value: Value) -> Result<Value, CommandError> {
+    loop {
+        // zustand's own `version` field is 0 as well, so a missing or zero version means legacy
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+        value = match version {
+            0 => migrate_from_frontend_store(value),
+            v if v == u64::from(SETTINGS_VERSION) => return Ok(value),
+            v => return Err(invalid(format!("settings version {} is newer than this app supports", v))),
+        };
+    }
+}
+
+fn parse(value: Value) -> Result<AppSettings, CommandError> {
+    serde_json::from_value(migrate(value)?).map_err(|e| invalid(e.to_string()))
+}
+
+/// Checks settings coming from the frontend. Installed fonts must be in the registry, whose
+/// label and format win over the ones sent.
+fn validate(settings: AppSettings, fonts_dir: &Path) -> Result<AppSettings, CommandError> {
+    let editor_font = match settings.editor_font {
+        EditorFont::Default => EditorFont::Default,
+        EditorFont::File { id, .. } => {
+            let font = fonts::registered_font(fonts_dir, &id)?;
+            EditorFont::File {
+                id: font.id,
+                label: font.label,
+                format: font.format,
+            }
+        }
+        EditorFont::System { family, label } => {
+            let family = family.trim().to_string();
+            if family.is_empty() {
+                return Err(invalid("system font family is empty"));
+            }
+            let label = if label.trim().is_empty() { family.clone() } else { label.trim().to_string() };
+            EditorFont::System { family, label }
+        }
+    };
+    if !(MIN_AUTOSAVE_DELAY_MS..=MAX_AUTOSAVE_DELAY_MS).contains(&settings.autosave.delay_ms) {
+        return Err(invalid(format!(
+            "autosave delay must be between {} and {} ms",
+            MIN_AUTOSAVE_DELAY_MS, MAX_AUTOSAVE_DELAY_MS
+        )));
+    }
+
+    Ok(AppSettings {
+        version: SETTINGS_VERSION,
+        editor_font,
+        autosave: settings.autosave,
+    })
+}
+
+/// Managed state holding the app settings
+pub struct SettingsState {
+    file: PathBuf,
+    settings: Mutex<AppSettings>,
+}
+
+impl SettingsState {
+    /// Reads `settings.json`, migrating it in place if it was written by an older version.
+    /// Settings that can't be read, including ones from a newer version, give the defaults;
+    /// the file is set aside and the error returned for reporting.
+    pub fn load(data_dir: &Path) -> (Self, Option<CommandError>) {
+        let file = data_dir.join(SETTINGS_FILE);
+        let (settings, error) = storage::load_or_default(&file, |file| {
+            let value: Value = read_json(file)?;
+            let settings = parse(value.clone())?;
+            if value.get("version").and_then(Value::as_u64) != Some(u64::from(SETTINGS_VERSION)) {
+                write_json(file, &settings)?;
+            }
+            Ok(settings)
+        });
+        let state = SettingsState {
+            file,
+            settings: Mutex::new(settings),
+        };
+        (state, error)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, AppSettings> {
+        self.settings.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn get(&self) -> AppSettings {
+        self.lock().clone()
+    }
+}
+
+#[tauri::command]
+pub fn get_settings(settings: State<'_, SettingsState>) -> AppSettings {
+    settings.get()
+}
+
+/// Validates and saves the settings. Documents from earlier versions, including the
+/// frontend's old localStorage settings, are migrated first.
+#[tauri::command]
+pub async fn update_settings(
+    app: tauri::AppHandle,
+    settings: State<'_, SettingsState>,
+    font_state: State<'_, FontState>,
+    new_settings: Value,
+) -> Result<AppSettings, CommandError> {
+    let parsed = parse(new_settings)?;
+    let validated = {
+        let _guard = font_state.lock();
+        validate(parsed, &fonts::fonts_dir(&app)?)?
+    };
+    let mut current = settings.lock();
+    write_json(&settings.file, &validated)?;
+    *current = validated.clone();
+    Ok(validated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frontend_store_migrates_to_version_1() {
+        let legacy = json!({
+            "state": {
+                "editorFont": { "type": "file", "id": "f1", "label": "Noto Sans JP", "format": "woff2" },
+                "installedFonts": [{ "id": "f1", "label": "Noto Sans JP", "format": "woff2" }],
+            },
+            "version": 0,
+        });
+        let settings = parse(legacy).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(
+            settings.editor_font,
+            EditorFont::File { id: "f1".to_string(), label: "Noto Sans JP".to_string(), format: "woff2".to_string() }
+        );
+        assert!(!settings.autosave.enabled);
+        assert_eq!(settings.autosave.delay_ms, 1000);
+
+        // A store from before any font was chosen has no editorFont (nor, on zustand's side, a version)
+        let settings = parse(json!({ "state": {} })).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.editor_font, EditorFont::Default);
+
+        assert!(parse(json!({ "version": SETTINGS_VERSION + 1 })).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::MemoKind;
+use crate::frontmatter;
+
+/// Longest excerpt returned by `list_memos`, in characters
+const EXCERPT_LENGTH: usize = 160;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Words of Latin-script text plus each CJK character, the way Japanese text is usually counted
+    pub words: usize,
+    /// Characters excluding whitespace
+    pub characters: usize,
+    pub headings: usize,
+    pub tasks: usize,
+    pub tasks_done: usize,
+}
+
+/// Plain-text excerpt and statistics of a memo's body
+#[derive(Debug, Clone, Default)]
+pub struct MemoSummary {
+    pub excerpt: String,
+    pub stats: MemoStats,
+}
+
+/// Han, kana and Hangul are written without spaces, so each character counts as a word
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // Hiragana, Katakana
+        | '\u{31F0}'..='\u{31FF}'   // Katakana phonetic extensions
+        | '\u{3400}'..='\u{4DBF}'   // CJK extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK unified ideographs
+        | '\u{F900}'..='\u{FAFF}'   // CJK compatibility ideographs
+        | '\u{FF66}'..='\u{FF9D}'   // Half-width katakana
+        | '\u{AC00}'..='\u{D7AF}'   // Hangul syllables
+        | '\u{20000}'..='\u{2FA1F}' // CJK extensions B and later
+    )
+}
+
+/// Japanese punctuation (、。「」, full-width ！？（） and the like) separates words, since
+/// no space follows it
+fn is_cjk_punctuation(c: char) -> bool {
+    matches!(c, '\u{3000}'..='\u{303F}') || (matches!(c, '\u{FF00}'..='\u{FFEF}') && !c.is_alphanumeric())
+}
+
+#[derive(Default)]
+struct TextCounter {
+    words: usize,
+    characters: usize,
+    in_word: bool,
+}
+
+impl TextCounter {
+    fn add(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_whitespace() {
+                self.in_word = false;
+                continue;
+            }
+            self.characters += 1;
+            // Punctuation neither starts nor splits a word ("don't", "e-mail"), except
+            // Japanese punctuation
+            if is_cjk(c) {
+                self.words += 1;
+                self.in_word = false;
+            } else if is_cjk_punctuation(c) {
+                self.in_word = false;
+            } else if c.is_alphanumeric() {
+                if !self.in_word {
+                    self.words += 1;
+                }
+                self.in_word = true;
+            }
+        }
+    }
+
+    /// Ends the current word at a block or inline boundary
+    fn boundary(&mut self) {
+        self.in_word = false;
+    }
+}
+
+#[derive(Default)]
+struct Excerpt {
+    text: String,
+    length: usize,
+}
+
+impl Excerpt {
+    fn is_full(&self) -> bool {
+        self.length >= EXCERPT_LENGTH
+    }
+
+    /// Appends text with whitespace collapsed to single spaces
+    fn add(&mut self, text: &str) {
+        for c in text.chars() {
+            if self.is_full() {
+                return;
+            }
+            if c.is_whitespace() {
+                if !self.text.is_empty() && !self.text.ends_with(' ') {
+                    self.text.push(' ');
+                    self.length += 1;
+                }
+            } else {
+                self.text.push(c);
+                self.length += 1;
+            }
+        }
+    }
+
+    fn finish(self) -> String {
+        self.text.trim_end().to_string()
+    }
+}
+
+fn summarize_text(content: &str) -> MemoSummary {
+    let mut counter = TextCounter::default();
+    let mut excerpt = Excerpt::default();
+    counter.add(content);
+    excerpt.add(content);
+    MemoSummary {
+        excerpt: excerpt.finish(),
+        stats: MemoStats {
+            words: counter.words,
+            characters: counter.characters,
+            ..MemoStats::default()
+        },
+    }
+}
+
+fn summarize_markdown(body: &str) -> MemoSummary {
+    let mut counter = TextCounter::default();
+    let mut excerpt = Excerpt::default();
+    let mut stats = MemoStats::default();
+    let mut in_code_block = false;
+
+    for event in Parser::new_ext(body, Options::ENABLE_TASKLISTS | Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => stats.headings += 1,
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            // Code blocks are not prose: they count neither as words nor as excerpt text
+            Event::Text(text) if !in_code_block => {
+                counter.add(&text);
+                excerpt.add(&text);
+            }
+            Event::Code(code) => {
+                counter.add(&code);
+                excerpt.add(&code);
+            }
+            Event::TaskListMarker(done) => {
+                stats.tasks += 1;
+                if done {
+                    stats.tasks_done += 1;
+                }
+            }
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::TableCell | TagEnd::BlockQuote(_)) => {
+                counter.boundary();
+                excerpt.add(" ");
+            }
+            _ => {}
+        }
+    }
+
+    stats.words = counter.words;
+    stats.characters = counter.characters;
+    MemoSummary {
+        excerpt: excerpt.finish(),
+        stats,
+    }
+}
+
+/// Summarizes a memo. Markdown is stripped from the excerpt and front matter is skipped;
+/// plain-text memos are taken as-is.
+pub fn summarize(content: &str, kind: MemoKind) -> MemoSummary {
+    match kind {
+        MemoKind::Text => summarize_text(content),
+        MemoKind::Markdown | MemoKind::Mdx => {
+            summarize_markdown(&content[frontmatter::parse(content).body_offset..])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cjk_characters_count_as_words_next_to_latin_words() {
+        let stats = summarize("Hello 世界 and カタカナ", MemoKind::Text).stats;
+        assert_eq!(stats.words, 1 + 2 + 1 + 4);
+        assert_eq!(stats.characters, 5 + 2 + 3 + 4);
+
+        let stats = summarize("メモをdon't forget、e-mailで送る", MemoKind::Text).stats;
+        assert_eq!(stats.words, 3 + 1 + 1 + 1 + 2 + 1);
+    }
+
+    #[test]
+    fn front_matter_is_skipped_in_markdown_only() {
+        let content = "---\ntitle: Weekly notes\ntags: [work]\n---\n# 見出し\n\nbody text\n";
+        let summary = summarize(content, MemoKind::Markdown);
+        assert_eq!(summary.stats.words, 3 + 2);
+        assert_eq!(summary.stats.headings, 1);
+        assert_eq!(summary.excerpt, "見出し body text");
+
+        let stats = summarize(content, MemoKind::Text).stats;
+        assert!(stats.words > 5);
+    }
+
+    #[test]
+    fn code_blocks_are_left_out_but_inline_code_counts() {
+        let content = "Before\n\n```rust\nfn main() {}\n```\n\n    indented code\n\nAfter `inline`\n";
+        let summary = summarize(content, MemoKind::Markdown);
+        assert_eq!(summary.stats.words, 3);
+        assert_eq!(summary.excerpt, "Before After inline");
+    }
+
+    #[test]
+    fn tasks_and_headings_are_counted() {
+        let content = "# 今日\n\n- [ ] 買い物\n- [x] email Bob\n\n## Later\n";
+        let stats = summarize(content, MemoKind::Markdown).stats;
+        assert_eq!((stats.headings, stats.tasks, stats.tasks_done), (2, 2, 1));
+        assert_eq!(stats.words, 2 + 3 + 2 + 1);
+    }
+}
@@ -0,0 +1,87 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{Manager, State};
+
+use crate::commands::CommandError;
+
+/// Resolves the app data directory where backend-owned state is persisted
+pub fn app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, CommandError> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| CommandError::PathError(e.to_string()))
+}
+
+/// Current time as Unix milliseconds
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Reads a JSON data file, returning the default value if it does not exist yet
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, CommandError> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let content = fs::read_to_string(path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| CommandError::InvalidData(format!("{}: {}", path.display(), e)))
+}
+
+/// Loads a data file at startup with `load`. A file that exists but can't be loaded doesn't
+/// stop the app: it is copied to `<name>.bak`, before the defaults overwrite it, and the
+/// defaults are used. The error is returned so it can be reported.
+pub fn load_or_default<T: Default>(
+    path: &Path,
+    load: impl FnOnce(&Path) -> Result<T, CommandError>,
+) -> (T, Option<CommandError>) {
+    if !path.exists() {
+        return (T::default(), None);
+    }
+    match load(path) {
+        Ok(value) => (value, None),
+        Err(error) => {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(".bak");
+            let error = match fs::copy(path, &backup) {
+                Ok(_) => CommandError::InvalidData(format!("{} (kept as {})", error, Path::new(&backup).display())),
+                Err(_) => error,
+            };
+            (T::default(), Some(error))
+        }
+    }
+}
+
+/// Errors from loading data files at startup, kept until the frontend can show them
+#[derive(Default)]
+pub struct StartupErrors(Mutex<Vec<String>>);
+
+impl StartupErrors {
+    pub fn new(errors: impl IntoIterator<Item = CommandError>) -> Self {
+        StartupErrors(Mutex::new(errors.into_iter().map(|error| error.to_string()).collect()))
+    }
+}
+
+/// Returns the startup errors once; later calls get an empty list
+#[tauri::command]
+pub fn take_startup_errors(state: State<'_, StartupErrors>) -> Vec<String> {
+    std::mem::take(&mut *state.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+}
+
+/// Writes a JSON data file through a temporary file so a crash never leaves it half-written
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), CommandError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content =
+        serde_json::to_string_pretty(value).map_err(|e| CommandError::InvalidData(e.to_string()))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
@@ -0,0 +1,707 @@
+use base64::Engine;
+use chrono::Local;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::State;
+use ureq::http::{Request, Response};
+use ureq::{Agent, Body};
+
+use crate::commands::{is_safe_filename, memo_kind_in, CommandError};
+use crate::crypto;
+use crate::git::GitState;
+use crate::storage::{now_millis, read_json, write_json};
+use crate::vault::{self, VaultState};
+
+/// Sync target of the vault, kept in the vault's backend data directory
+const SYNC_FILE: &str = "sync.json";
+/// State of every file as of the last sync, used to tell which side changed
+const SYNC_STATE_FILE: &str = "sync-state.json";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Largest remote file downloaded
+const MAX_REMOTE_FILE_SIZE: u64 = 64 * 1024 * 1024;
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:getetag/><d:getlastmodified/><d:getcontentlength/><d:resourcetype/></d:prop></d:propfind>"#;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SyncTarget {
+    /// Another directory, e.g. a mounted network share or a cloud drive folder
+    Directory { path: String },
+    /// A WebDAV collection; memos are stored directly inside it
+    Webdav {
+        url: String,
+        username: Option<String>,
+        /// Never sent back to the webview
+        password: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub target: Option<SyncTarget>,
+    /// Unix ms of the last successful sync
+    pub last_synced_at: Option<u64>,
+}
+
+impl SyncConfig {
+    /// The config as shown to the webview, with the WebDAV password left out
+    fn redacted(mut self) -> Self {
+        if let Some(SyncTarget::Webdav { password, .. }) = &mut self.target {
+            *password = None;
+        }
+        self
+    }
+}
+
+/// Versions of a file on both sides right after it was last synced
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct FileBase {
+    local: String,
+    remote: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct SyncBases {
+    files: BTreeMap<String, FileBase>,
+}
+
+/// File names touched by a sync, per kind of change
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncReport {
+    pub uploaded: Vec<String>,
+    pub downloaded: Vec<String>,
+    pub deleted_local: Vec<String>,
+    pub deleted_remote: Vec<String>,
+    /// Conflict copies holding the remote side of memos changed on both sides
+    pub conflicts: Vec<String>,
+    /// Memos edited locally while the sync ran; they are picked up by the next sync
+    pub skipped: Vec<String>,
+}
+
+/// Managed state making sure only one sync runs at a time
+#[derive(Default)]
+pub struct SyncState(Arc<Mutex<()>>);
+
+fn sync_error(error: impl std::fmt::Display) -> CommandError {
+    CommandError::SyncError(error.to_string())
+}
+
+/// A flat store of memo files, keyed by file name. Versions are opaque strings that change
+/// whenever the content does.
+trait SyncBackend {
+    fn list(&self) -> Result<HashMap<String, String>, CommandError>;
+    fn read(&self, name: &str) -> Result<Vec<u8>, CommandError>;
+    fn write(&self, name: &str, content: &[u8]) -> Result<(), CommandError>;
+    fn delete(&self, name: &str) -> Result<(), CommandError>;
+}
+
+/// Only memos with one of the vault's extensions and a plain file name are synced, plus the
+/// key of the encrypted memos
+fn is_synced_name(name: &str, extensions: &[String]) -> bool {
+    name == crypto::KEY_FILE
+        || !name.trim().is_empty() && is_safe_filename(name) && memo_kind_in(Path::new(name), extensions).is_some()
+}
+
+struct DirectoryBackend {
+    root: PathBuf,
+    extensions: Vec<String>,
+}
+
+impl DirectoryBackend {
+    fn version(&self, name: &str) -> Option<String> {
+        let metadata = fs::metadata(self.root.join(name)).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_millis();
+        Some(format!("{}:{}", modified, metadata.len()))
+    }
+}
+
+impl SyncBackend for DirectoryBackend {
+    fn list(&self) -> Result<HashMap<String, String>, CommandError> {
+        let mut files = HashMap::new();
+        for entry in fs::read_dir(&self.root)?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_file() && is_synced_name(&name, &self.extensions) {
+                if let Some(version) = self.version(&name) {
+                    files.insert(name, version);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn read(&self, name: &str) -> Result<Vec<u8>, CommandError> {
+        Ok(fs::read(self.root.join(name))?)
+    }
+
+    /// Writes through a temporary file so the other side never sees a half-written memo
+    fn write(&self, name: &str, content: &[u8]) -> Result<(), CommandError> {
+        let tmp_path = self.root.join(format!(".{}.sync-tmp", name));
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, self.root.join(name))?;
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), CommandError> {
+        match fs::remove_file(self.root.join(name)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+struct WebDavBackend {
+    agent: Agent,
+    /// Collection URL, always ending in `/`
+    base_url: String,
+    authorization: Option<String>,
+    extensions: Vec<String>,
+}
+
+/// Percent-encodes a file name for use as a URL path segment
+fn encode_segment(name: &str) -> String {
+    name.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn decode_segment(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) if bytes[i] == b'%' => {
+                decoded.push(byte);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[derive(Default)]
+struct DavEntry {
+    href: String,
+    etag: String,
+    last_modified: String,
+    length: String,
+    is_collection: bool,
+}
+
+/// Parses a PROPFIND multistatus response into file name → version
+fn parse_multistatus(xml: &str, extensions: &[String]) -> Result<HashMap<String, String>, CommandError> {
+    let mut reader = Reader::from_str(xml);
+    let mut files = HashMap::new();
+    let mut entry = DavEntry::default();
+    let mut field: Option<String> = None;
+
+    let push_text = |entry: &mut DavEntry, field: &Option<String>, text: &str| match field.as_deref() {
+        Some("href") => entry.href.push_str(text),
+        Some("getetag") => entry.etag.push_str(text),
+        Some("getlastmodified") => entry.last_modified.push_str(text),
+        Some("getcontentlength") => entry.length.push_str(text),
+        _ => {}
+    };
+
+    loop {
+        match reader.read_event().map_err(sync_error)? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                "response" => entry = DavEntry::default(),
+                "collection" => entry.is_collection = true,
+                name => field = Some(name.to_string()),
+            },
+            Event::Empty(element) if element.local_name().as_ref() == "collection" => entry.is_collection = true,
+            Event::Text(text) => push_text(&mut entry, &field, &text.xml10_content()),
+            Event::CData(data) => push_text(&mut entry, &field, &data.xml10_content()),
+            Event::GeneralRef(reference) => {
+                let resolved = match reference.resolve_char_ref().map_err(sync_error)? {
+                    Some(c) => c.to_string(),
+                    None => quick_xml::escape::resolve_predefined_entity(&reference).unwrap_or_default().to_string(),
+                };
+                push_text(&mut entry, &field, &resolved);
+            }
+            Event::End(element) => {
+                if element.local_name().as_ref() == "response" && !entry.is_collection {
+                    let href = entry.href.trim();
+                    let name = decode_segment(href.rsplit('/').next().unwrap_or_default());
+                    if is_synced_name(&name, extensions) {
+                        let etag = entry.etag.trim();
+                        let version = if etag.is_empty() {
+                            format!("{}:{}", entry.last_modified.trim(), entry.length.trim())
+                        } else {
+                            etag.to_string()
+                        };
+                        files.insert(name, version);
+                    }
+                }
+                field = None;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(files)
+}
+
+impl WebDavBackend {
+    fn new(url: &str, username: Option<&str>, password: Option<&str>, extensions: Vec<String>) -> Self {
+        let config = Agent::config_builder()
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            // PROPFIND isn't an HTTP/1.1 method, so ureq refuses it by default
+            .allow_non_standard_methods(true)
+            .build();
+        let authorization = username.map(|username| {
+            let credentials = format!("{}:{}", username, password.unwrap_or_default());
+            format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials))
+        });
+        WebDavBackend {
+            agent: config.into(),
+            base_url: format!("{}/", url.trim_end_matches('/')),
+            authorization,
+            extensions,
+        }
+    }
+
+    /// Sends a request; HTTP error statuses come back as `ureq::Error::StatusCode`
+    fn send<B: ureq::AsSendBody>(&self, method: &str, url: &str, depth: Option<&str>, body: B) -> Result<Response<Body>, ureq::Error> {
+        let mut request = Request::builder().method(method).uri(url);
+        if let Some(authorization) = &self.authorization {
+            request = request.header("Authorization", authorization);
+        }
+        if let Some(depth) = depth {
+            request = request
+                .header("Depth", depth)
+                .header("Content-Type", "application/xml; charset=utf-8");
+        }
+        self.agent.run(request.body(body)?)
+    }
+
+    fn file_url(&self, name: &str) -> String {
+        format!("{}{}", self.base_url, encode_segment(name))
+    }
+}
+
+impl SyncBackend for WebDavBackend {
+    fn list(&self) -> Result<HashMap<String, String>, CommandError> {
+        let mut response = self.send("PROPFIND", &self.base_url, Some("1"), PROPFIND_BODY).map_err(sync_error)?;
+        let xml = response.body_mut().read_to_string().map_err(sync_error)?;
+        parse_multistatus(&xml, &self.extensions)
+    }
+
+    fn read(&self, name: &str) -> Result<Vec<u8>, CommandError> {
+        let mut response = self.send("GET", &self.file_url(name), None, ()).map_err(sync_error)?;
+        response
+            .body_mut()
+            .with_config()
+            .limit(MAX_REMOTE_FILE_SIZE)
+            .read_to_vec()
+            .map_err(sync_error)
+    }
+
+    fn write(&self, name: &str, content: &[u8]) -> Result<(), CommandError> {
+        self.send("PUT", &self.file_url(name), None, content).map_err(sync_error)?;
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), CommandError> {
+        match self.send("DELETE", &self.file_url(name), None, ()) {
+            // Already gone from the server
+            Ok(_) | Err(ureq::Error::StatusCode(404)) => Ok(()),
+            Err(error) => Err(sync_error(error)),
+        }
+    }
+}
+
+/// `memo (conflict 2026-10-18 153000).md`
+fn conflict_name(name: &str) -> String {
+    let path = Path::new(name);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    format!("{} (conflict {}).{}", stem, Local::now().format("%Y-%m-%d %H%M%S"), ext)
+}
+
+/// Two-way sync of a flat folder. A side counts as changed when its version differs from
+/// the one recorded at the last sync. Changes on one side are copied to the other, deletions
+/// are propagated, an edit wins over a deletion, and memos changed on both sides keep the
+/// local content while the remote content is saved as a conflict copy next to it.
+fn sync_files(local: &DirectoryBackend, remote: &dyn SyncBackend, bases: &mut SyncBases) -> Result<SyncReport, CommandError> {
+    let local_files = local.list()?;
+    let remote_files = remote.list()?;
+    let names: BTreeSet<String> = local_files
+        .keys()
+        .chain(remote_files.keys())
+        .chain(bases.files.keys())
+        .cloned()
+        .collect();
+
+    let mut report = SyncReport::default();
+    // Uploaded files whose remote version is only known after listing again
+    let mut uploaded: Vec<String> = Vec::new();
+    let mut upload = |name: &str, content: &[u8], report: &mut SyncReport| -> Result<(), CommandError> {
+        remote.write(name, content)?;
+        uploaded.push(name.to_string());
+        report.uploaded.push(name.to_string());
+        Ok(())
+    };
+
+    for name in names {
+        let (local_version, remote_version) = (local_files.get(&name), remote_files.get(&name));
+        let base = bases.files.get(&name);
+        let local_changed = local_version != base.map(|base| &base.local);
+        let remote_changed = remote_version != base.map(|base| &base.remote);
+        // The local memo may have been saved since it was listed; never clobber that edit
+        let unchanged_locally = || local.version(&name).as_ref() == local_version;
+
+        match (local_changed, remote_changed, local_version, remote_version) {
+            (false, false, _, _) => {}
+            (_, _, None, None) => {
+                bases.files.remove(&name);
+            }
+            (true, false, Some(_), _) | (true, true, Some(_), None) => {
+                upload(&name, &local.read(&name)?, &mut report)?;
+            }
+            (true, false, None, Some(_)) => {
+                remote.delete(&name)?;
+                bases.files.remove(&name);
+                report.deleted_remote.push(name);
+            }
+            (false, true, Some(_), None) => {
+                if !unchanged_locally() {
+                    report.skipped.push(name);
+                    continue;
+                }
+                local.delete(&name)?;
+                bases.files.remove(&name);
+                report.deleted_local.push(name);
+            }
+            (_, true, None, Some(remote_version)) | (false, true, Some(_), Some(remote_version)) => {
+                if !unchanged_locally() {
+                    report.skipped.push(name);
+                    continue;
+                }
+                local.write(&name, &remote.read(&name)?)?;
+                if let Some(local_version) = local.version(&name) {
+                    bases.files.insert(name.clone(), FileBase { local: local_version, remote: remote_version.clone() });
+                }
+                report.downloaded.push(name);
+            }
+            (true, true, Some(local_version), Some(remote_version)) => {
+                let (local_content, remote_content) = (local.read(&name)?, remote.read(&name)?);
+                if local_content != remote_content {
+                    let copy = conflict_name(&name);
+                    local.write(&copy, &remote_content)?;
+                    upload(&copy, &remote_content, &mut report)?;
+                    upload(&name, &local_content, &mut report)?;
+                    report.conflicts.push(copy);
+                }
+                bases.files.insert(name, FileBase { local: local_version.clone(), remote: remote_version.clone() });
+            }
+        }
+    }
+
+    if !uploaded.is_empty() {
+        let remote_files = remote.list()?;
+        for name in uploaded {
+            match (local.version(&name), remote_files.get(&name)) {
+                (Some(local), Some(remote)) => {
+                    bases.files.insert(name, FileBase { local, remote: remote.clone() });
+                }
+                _ => {
+                    bases.files.remove(&name);
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Writes `sync.json`. It may hold the WebDAV password in plain text, so on Unix it is made
+/// readable by the current user only.
+fn write_config(vault_dir: &Path, config: &SyncConfig) -> Result<(), CommandError> {
+    let file = vault_dir.join(SYNC_FILE);
+    write_json(&file, config)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+fn sync_file(vaults: &VaultState) -> Result<PathBuf, CommandError> {
+    Ok(vaults.lock().active_vault_dir()?.join(SYNC_FILE))
+}
+
+/// Saves a new sync target; the per-file state belongs to the old target and is dropped
+fn set_target(vaults: &VaultState, target: Option<SyncTarget>) -> Result<SyncConfig, CommandError> {
+    let vault_dir = vaults.lock().active_vault_dir()?;
+    let config = SyncConfig {
+        target,
+        last_synced_at: None,
+    };
+    write_config(&vault_dir, &config)?;
+    let state_file = vault_dir.join(SYNC_STATE_FILE);
+    if state_file.exists() {
+        fs::remove_file(state_file)?;
+    }
+    Ok(config.redacted())
+}
+
+#[tauri::command]
+pub fn get_sync_config(state: State<'_, VaultState>) -> Result<SyncConfig, CommandError> {
+    let config: SyncConfig = read_json(&sync_file(&state)?)?;
+    Ok(config.redacted())
+}
+
+/// Picks the directory to sync with via the native dialog, so the webview never supplies a raw path
+#[tauri::command]
+pub async fn pick_sync_folder(app: tauri::AppHandle, state: State<'_, VaultState>) -> Result<Option<SyncConfig>, CommandError> {
+    let Some(folder) = vault::pick_folder(&app).await? else {
+        return Ok(None);
+    };
+    let folder = folder.canonicalize()?;
+    if folder == state.lock().active_folder()? {
+        return Err(CommandError::InvalidFolder("cannot sync the working folder with itself".to_string()));
+    }
+    let target = SyncTarget::Directory {
+        path: folder.to_string_lossy().to_string(),
+    };
+    set_target(&state, Some(target)).map(Some)
+}
+
+/// Syncs with a WebDAV collection. The password is stored in plain text in the vault's
+/// `sync.json` in the app data directory (readable only by the current user on Unix); it is
+/// never returned to the webview.
+#[tauri::command]
+pub fn set_sync_webdav(
+    state: State<'_, VaultState>,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+) -> Result<SyncConfig, CommandError> {
+    let url = url.trim().to_string();
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(CommandError::SyncError(format!("not an http(s) URL: {}", url)));
+    }
+    let username = username.filter(|username| !username.is_empty());
+    set_target(&state, Some(SyncTarget::Webdav { url, username, password }))
+}
+
+#[tauri::command]
+pub fn clear_sync(state: State<'_, VaultState>) -> Result<(), CommandError> {
+    set_target(&state, None).map(|_| ())
+}
+
+/// Runs a two-way sync of the working folder with its sync target
+#[tauri::command]
+pub async fn sync_now(
+    vaults: State<'_, VaultState>,
+    git: State<'_, GitState>,
+    sync: State<'_, SyncState>,
+) -> Result<SyncReport, CommandError> {
+    let (folder, extensions, vault_dir) = {
+        let registry = vaults.lock();
+        (registry.active_folder()?, registry.active_settings()?.extensions, registry.active_vault_dir()?)
+    };
+    let running = Arc::clone(&sync.0);
+
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let Ok(_guard) = running.try_lock() else {
+            return Err(CommandError::SyncError("a sync is already running".to_string()));
+        };
+        let mut config: SyncConfig = read_json(&vault_dir.join(SYNC_FILE))?;
+        let remote: Box<dyn SyncBackend> = match config.target.clone() {
+            None => return Err(CommandError::SyncError("no sync target is configured".to_string())),
+            Some(SyncTarget::Directory { path }) => {
+                let root = PathBuf::from(&path);
+                if !root.is_dir() {
+                    return Err(CommandError::InvalidFolder(path));
+                }
+                Box::new(DirectoryBackend { root, extensions: extensions.clone() })
+            }
+            Some(SyncTarget::Webdav { url, username, password }) => Box::new(WebDavBackend::new(
+                &url,
+                username.as_deref(),
+                password.as_deref(),
+                extensions.clone(),
+            )),
+        };
+        let local = DirectoryBackend { root: folder, extensions };
+
+        let state_file = vault_dir.join(SYNC_STATE_FILE);
+        let mut bases: SyncBases = read_json(&state_file)?;
+        let result = sync_files(&local, remote.as_ref(), &mut bases);
+        // Keep what was done before a failure, so the next sync picks up from there
+        write_json(&state_file, &bases)?;
+        let report = result?;
+
+        config.last_synced_at = Some(now_millis());
+        write_config(&vault_dir, &config)?;
+        Ok(report)
+    })
+    .await
+    .map_err(sync_error)??;
+
+    if !(report.downloaded.is_empty() && report.deleted_local.is_empty() && report.conflicts.is_empty()) {
+        git.record_change(&vaults.lock());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    type RemoteFiles = Arc<Mutex<BTreeMap<String, (Vec<u8>, u32)>>>;
+
+    /// Minimal in-process WebDAV server: one flat collection at `/dav/`, one request per connection
+    fn start_webdav() -> (String, RemoteFiles) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/dav", listener.local_addr().unwrap());
+        let files: RemoteFiles = Arc::default();
+        let served = Arc::clone(&files);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                handle(stream, &served);
+            }
+        });
+        (url, files)
+    }
+
+    fn handle(stream: TcpStream, files: &RemoteFiles) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut parts = request_line.split_whitespace();
+        let (method, path) = (parts.next().unwrap().to_string(), parts.next().unwrap().to_string());
+
+        let (mut length, mut chunked) = (0, false);
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end().to_ascii_lowercase();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+            if line.starts_with("transfer-encoding:") && line.contains("chunked") {
+                chunked = true;
+            }
+        }
+        let mut body = Vec::new();
+        if chunked {
+            loop {
+                let mut size = String::new();
+                reader.read_line(&mut size).unwrap();
+                let size = usize::from_str_radix(size.trim(), 16).unwrap();
+                let mut chunk = vec![0; size + 2];
+                reader.read_exact(&mut chunk).unwrap();
+                if size == 0 {
+                    break;
+                }
+                body.extend_from_slice(&chunk[..size]);
+            }
+        } else {
+            body.resize(length, 0);
+            reader.read_exact(&mut body).unwrap();
+        }
+
+        let name = decode_segment(path.trim_start_matches("/dav").trim_start_matches('/'));
+        let mut files = files.lock().unwrap();
+        let (status, response) = match method.as_str() {
+            "PROPFIND" => {
+                let mut xml = String::from(
+                    r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:"><d:response><d:href>/dav/</d:href><d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat></d:response>"#,
+                );
+                for (name, (content, version)) in files.iter() {
+                    xml.push_str(&format!(
+                        r#"<d:response><d:href>/dav/{}</d:href><d:propstat><d:prop><d:getetag>"{}"</d:getetag><d:getcontentlength>{}</d:getcontentlength><d:resourcetype/></d:prop></d:propstat></d:response>"#,
+                        encode_segment(name),
+                        version,
+                        content.len()
+                    ));
+                }
+                xml.push_str("</d:multistatus>");
+                ("207 Multi-Status", xml.into_bytes())
+            }
+            "GET" => match files.get(&name) {
+                Some((content, _)) => ("200 OK", content.clone()),
+                None => ("404 Not Found", Vec::new()),
+            },
+            "PUT" => {
+                let version = files.get(&name).map_or(1, |(_, version)| version + 1);
+                files.insert(name, (body, version));
+                ("201 Created", Vec::new())
+            }
+            "DELETE" => match files.remove(&name) {
+                Some(_) => ("204 No Content", Vec::new()),
+                None => ("404 Not Found", Vec::new()),
+            },
+            _ => ("405 Method Not Allowed", Vec::new()),
+        };
+        let mut stream = stream;
+        write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, response.len()).unwrap();
+        stream.write_all(&response).unwrap();
+    }
+
+    fn temp_folder() -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("memo-sync-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    #[test]
+    fn syncs_with_webdav() {
+        let (url, remote_files) = start_webdav();
+        let extensions = vec!["md".to_string()];
+        let root = temp_folder();
+        let local = DirectoryBackend { root: root.clone(), extensions: extensions.clone() };
+        let remote = WebDavBackend::new(&url, Some("user"), Some("secret"), extensions);
+        let mut bases = SyncBases::default();
+
+        fs::write(root.join("local.md"), "local").unwrap();
+        remote_files.lock().unwrap().insert("remote memo.md".to_string(), (b"remote".to_vec(), 1));
+        let report = sync_files(&local, &remote, &mut bases).unwrap();
+        assert_eq!(report.uploaded, ["local.md"]);
+        assert_eq!(report.downloaded, ["remote memo.md"]);
+        assert_eq!(remote_files.lock().unwrap()["local.md"].0, b"local");
+        assert_eq!(fs::read_to_string(root.join("remote memo.md")).unwrap(), "remote");
+
+        let report = sync_files(&local, &remote, &mut bases).unwrap();
+        assert!(report.uploaded.is_empty() && report.downloaded.is_empty());
+
+        fs::remove_file(root.join("local.md")).unwrap();
+        remote_files.lock().unwrap().insert("remote memo.md".to_string(), (b"remote, edited".to_vec(), 2));
+        let report = sync_files(&local, &remote, &mut bases).unwrap();
+        assert_eq!(report.deleted_remote, ["local.md"]);
+        assert_eq!(report.downloaded, ["remote memo.md"]);
+        assert!(!remote_files.lock().unwrap().contains_key("local.md"));
+        assert_eq!(fs::read_to_string(root.join("remote memo.md")).unwrap(), "remote, edited");
+
+        // Deleting a file that is already gone from the server isn't an error
+        remote.delete("missing.md").unwrap();
+        fs::remove_dir_all(root).unwrap();
+    }
+}
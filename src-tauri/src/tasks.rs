@@ -0,0 +1,445 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use tauri::State;
+
+use crate::autosave::AutosaveState;
+use crate::commands::{build_memo_meta, scan_memos, validate_memo_path, CommandError, MemoMeta, DEFAULT_MAX_MEMO_SIZE};
+use crate::crypto;
+use crate::encoding;
+use crate::frontmatter;
+use crate::git::GitState;
+use crate::ids;
+use crate::index::IndexState;
+use crate::vault::VaultState;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DUE_EMOJI: &str = "📅";
+
+/// Task priority, using the emoji markers of the Obsidian Tasks plugin
+/// (🔺 highest, ⏫ high, 🔼 medium, 🔽 low, ⏬ lowest) or `priority:<name>`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskPriority {
+    Lowest,
+    Low,
+    Medium,
+    High,
+    Highest,
+}
+
+impl TaskPriority {
+    fn from_marker(marker: &str) -> Option<Self> {
+        match marker {
+            "🔺" | "highest" => Some(TaskPriority::Highest),
+            "⏫" | "high" => Some(TaskPriority::High),
+            "🔼" | "medium" => Some(TaskPriority::Medium),
+            "🔽" | "low" => Some(TaskPriority::Low),
+            "⏬" | "lowest" => Some(TaskPriority::Lowest),
+            _ => None,
+        }
+    }
+
+    /// Sort rank; a task without a priority ranks between medium and low
+    fn rank(priority: Option<Self>) -> u8 {
+        match priority {
+            Some(TaskPriority::Highest) => 5,
+            Some(TaskPriority::High) => 4,
+            Some(TaskPriority::Medium) => 3,
+            None => 2,
+            Some(TaskPriority::Low) => 1,
+            Some(TaskPriority::Lowest) => 0,
+        }
+    }
+}
+
+/// A GFM task item (`- [ ] text`) found in a memo
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskItem {
+    /// 1-based line number, as passed to `toggle_task`
+    pub line: usize,
+    /// Text after the checkbox, including any due date, priority and tag markers
+    pub text: String,
+    pub done: bool,
+    /// `YYYY-MM-DD`, from `📅 2026-10-20` or `due:2026-10-20`
+    pub due: Option<String>,
+    pub priority: Option<TaskPriority>,
+    /// `#tag` markers, without the `#`
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Task {
+    pub memo: MemoMeta,
+    #[serde(flatten)]
+    pub item: TaskItem,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    #[default]
+    All,
+    Open,
+    Done,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct TaskFilter {
+    pub status: TaskStatus,
+    /// Only tasks due on or before this date (`YYYY-MM-DD`)
+    pub due_before: Option<String>,
+    /// Only tasks due on or after this date (`YYYY-MM-DD`)
+    pub due_after: Option<String>,
+    /// Only tasks carrying all of these tags
+    pub tags: Vec<String>,
+    /// Only tasks with at least this priority
+    pub min_priority: Option<TaskPriority>,
+    /// Case-insensitive substring of the task text
+    pub query: Option<String>,
+    /// Only tasks of this memo
+    pub file_path: Option<String>,
+}
+
+/// Returns whether the line is a task item, and the byte offset of its `[` checkbox
+fn parse_checkbox(line: &str) -> Option<(bool, usize)> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+
+    let marker_len = if rest.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if !(1..=9).contains(&digits) || !rest[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+    let after_marker = &rest[marker_len..];
+    let spaces = after_marker.len() - after_marker.trim_start_matches([' ', '\t']).len();
+    if spaces == 0 {
+        return None;
+    }
+
+    let checkbox = indent + marker_len + spaces;
+    let done = match line.get(checkbox..checkbox + 3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    // The checkbox must be followed by whitespace or end the line
+    match line[checkbox + 3..].chars().next() {
+        None | Some(' ') | Some('\t') => Some((done, checkbox)),
+        _ => None,
+    }
+}
+
+fn parse_date(value: &str) -> Option<String> {
+    let value = value.get(..10)?;
+    NaiveDate::parse_from_str(value, DATE_FORMAT).ok()?;
+    Some(value.to_string())
+}
+
+fn parse_due(text: &str) -> Option<String> {
+    if let Some(pos) = text.find(DUE_EMOJI) {
+        if let Some(date) = parse_date(text[pos + DUE_EMOJI.len()..].trim_start()) {
+            return Some(date);
+        }
+    }
+    text.split_whitespace()
+        .enumerate()
+        .find_map(|(i, word)| {
+            let value = word.to_ascii_lowercase().strip_prefix("due:")?.to_string();
+            if value.is_empty() {
+                // `due: 2026-10-20`
+                text.split_whitespace().nth(i + 1).and_then(parse_date)
+            } else {
+                parse_date(&value)
+            }
+        })
+}
+
+fn parse_priority(text: &str) -> Option<TaskPriority> {
+    ["🔺", "⏫", "🔼", "🔽", "⏬"]
+        .into_iter()
+        .find(|marker| text.contains(marker))
+        .and_then(TaskPriority::from_marker)
+        .or_else(|| {
+            text.split_whitespace().find_map(|word| {
+                TaskPriority::from_marker(word.to_ascii_lowercase().strip_prefix("priority:")?)
+            })
+        })
+}
+
+/// `#tag` markers: letters, digits, `_`, `-` and `/`, with at least one non-digit
+fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let Some(tag) = word.strip_prefix('#') else {
+            continue;
+        };
+        let tag: String = tag
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+            .collect();
+        let tag = tag.trim_end_matches(['-', '/']);
+        if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+/// Extracts the task items of a memo, skipping front matter and fenced code blocks
+pub fn parse_tasks(content: &str) -> Vec<TaskItem> {
+    let front_matter_lines = content[..frontmatter::parse(content).body_offset].lines().count();
+    let mut in_code_block = false;
+    let mut tasks = Vec::new();
+
+    for (index, line) in content.lines().enumerate().skip(front_matter_lines) {
+        if is_fence(line) {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        let Some((done, checkbox)) = parse_checkbox(line) else {
+            continue;
+        };
+        let text = line[checkbox + 3..].trim().to_string();
+        tasks.push(TaskItem {
+            line: index + 1,
+            due: parse_due(&text),
+            priority: parse_priority(&text),
+            tags: parse_tags(&text),
+            text,
+            done,
+        });
+    }
+    tasks
+}
+
+/// Ticks or unticks the task on a 1-based line of LF-normalized content. Returns the new
+/// content and the task as it is after the toggle.
+fn toggle_line(content: &str, line: usize) -> Result<(String, TaskItem), CommandError> {
+    // Only lines outside front matter and code blocks are tasks
+    let mut item = parse_tasks(content)
+        .into_iter()
+        .find(|item| item.line == line)
+        .ok_or(CommandError::NotATask(line))?;
+
+    let mut lines: Vec<&str> = content.split('\n').collect();
+    let source = lines[line - 1];
+    let (_, checkbox) = parse_checkbox(source).ok_or(CommandError::NotATask(line))?;
+    let mark = if item.done { " " } else { "x" };
+    let toggled = format!("{}[{}]{}", &source[..checkbox], mark, &source[checkbox + 3..]);
+    lines[line - 1] = &toggled;
+
+    item.done = !item.done;
+    Ok((lines.join("\n"), item))
+}
+
+fn validate_date(value: Option<&str>) -> Result<(), CommandError> {
+    match value {
+        Some(date) if parse_date(date).is_none() || date.len() != 10 => Err(CommandError::InvalidDate(date.to_string())),
+        _ => Ok(()),
+    }
+}
+
+impl TaskFilter {
+    fn matches(&self, task: &TaskItem) -> bool {
+        let status = match self.status {
+            TaskStatus::All => true,
+            TaskStatus::Open => !task.done,
+            TaskStatus::Done => task.done,
+        };
+        // ISO dates compare correctly as strings
+        let due_before = self
+            .due_before
+            .as_deref()
+            .is_none_or(|before| task.due.as_deref().is_some_and(|due| due <= before));
+        let due_after = self
+            .due_after
+            .as_deref()
+            .is_none_or(|after| task.due.as_deref().is_some_and(|due| due >= after));
+        let tags = self
+            .tags
+            .iter()
+            .all(|tag| task.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim_start_matches('#'))));
+        let priority = self
+            .min_priority
+            .is_none_or(|min| task.priority.is_some_and(|priority| priority >= min));
+        let query = self
+            .query
+            .as_deref()
+            .is_none_or(|query| task.text.to_lowercase().contains(&query.to_lowercase()));
+        status && due_before && due_after && tags && priority && query
+    }
+}
+
+/// Lists the task items of all memos in the working folder: open tasks first, then by due
+/// date (undated last), priority, most recently modified memo and line
+#[tauri::command]
+pub fn list_tasks(
+    vaults: State<'_, VaultState>,
+    index: State<'_, IndexState>,
+    filter: Option<TaskFilter>,
+) -> Result<Vec<Task>, CommandError> {
+    let filter = filter.unwrap_or_default();
+    validate_date(filter.due_before.as_deref())?;
+    validate_date(filter.due_after.as_deref())?;
+
+    let (vault, folder, settings) = {
+        let registry = vaults.lock();
+        (registry.active_vault()?.id.clone(), registry.active_folder()?, registry.active_settings()?)
+    };
+    let mut memos = match filter.file_path.as_deref() {
+        Some(file_path) => {
+            // Security: Validate extension and that the file is within the working folder
+            let (path, kind) = validate_memo_path(&folder, file_path)?;
+            let mut meta = build_memo_meta(&path, kind);
+            meta.path = file_path.to_string();
+            vec![meta]
+        }
+        None => scan_memos(&folder, &settings.extensions)?,
+    };
+    ids::assign_ids(&vaults.lock(), &mut memos)?;
+
+    let mut tasks = Vec::new();
+    index.with(&vault, |index| {
+        for memo in memos {
+            let items = index.tasks(&memo);
+            tasks.extend(
+                items
+                    .into_iter()
+                    .filter(|item| filter.matches(item))
+                    .map(|item| Task {
+                        memo: memo.clone(),
+                        item,
+                    }),
+            );
+        }
+    });
+
+    tasks.sort_by(|a, b| {
+        a.item
+            .done
+            .cmp(&b.item.done)
+            .then_with(|| match (&a.item.due, &b.item.due) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            })
+            .then_with(|| Reverse(TaskPriority::rank(a.item.priority)).cmp(&Reverse(TaskPriority::rank(b.item.priority))))
+            .then_with(|| b.memo.modified_at.cmp(&a.memo.modified_at))
+            .then_with(|| a.item.line.cmp(&b.item.line))
+    });
+    Ok(tasks)
+}
+
+/// Ticks or unticks the task on a 1-based line, editing the memo in place with its
+/// encoding and line endings preserved. Fails with `not_a_task` if the line has changed.
+#[tauri::command]
+pub fn toggle_task(
+    vaults: State<'_, VaultState>,
+    git: State<'_, GitState>,
+    autosave: State<'_, AutosaveState>,
+    file_path: String,
+    line: usize,
+) -> Result<Task, CommandError> {
+    let folder = vaults.lock().active_folder()?;
+    // Security: Validate extension and that the file is within the working folder
+    let (path, kind) = validate_memo_path(&folder, &file_path)?;
+    // Tasks of encrypted memos are never listed, so there is nothing to toggle
+    if crypto::is_encrypted(&path) {
+        return Err(CommandError::MemoEncrypted);
+    }
+
+    // Toggle on top of the latest edits rather than have a queued autosave overwrite the toggle
+    autosave.flush_memo(&folder, &file_path);
+    // Same limit as read_memo; the index doesn't list tasks of larger memos either
+    let size = fs::metadata(&path)?.len();
+    if size > DEFAULT_MAX_MEMO_SIZE {
+        return Err(CommandError::MemoTooLarge(DEFAULT_MAX_MEMO_SIZE, size));
+    }
+    let decoded = encoding::decode(&fs::read(&path)?)?;
+    let (content, item) = toggle_line(&decoded.content, line)?;
+    fs::write(&path, encoding::encode(&content, &decoded.format)?)?;
+
+    let mut memo = build_memo_meta(&path, kind);
+    memo.path = file_path;
+    // Bookkeeping only: the task is already toggled
+    let registry = vaults.lock();
+    let _ = ids::assign_id(&registry, &mut memo);
+    git.record_change(&registry);
+    Ok(Task { memo, item })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkboxes_are_recognized_after_list_markers() {
+        assert_eq!(parse_checkbox("- [ ] open"), Some((false, 2)));
+        assert_eq!(parse_checkbox("  * [x] done"), Some((true, 4)));
+        assert_eq!(parse_checkbox("+ [X]"), Some((true, 2)));
+        assert_eq!(parse_checkbox("12. [ ] numbered"), Some((false, 4)));
+        assert_eq!(parse_checkbox("1) [ ] numbered"), Some((false, 3)));
+        assert_eq!(parse_checkbox("-[ ] no space"), None);
+        assert_eq!(parse_checkbox("- [ ]text"), None);
+        assert_eq!(parse_checkbox("- [-] other mark"), None);
+        assert_eq!(parse_checkbox("[ ] no marker"), None);
+        assert_eq!(parse_checkbox("1234567890. [ ] too many digits"), None);
+    }
+
+    #[test]
+    fn due_dates_come_from_the_emoji_or_due_prefix() {
+        assert_eq!(parse_due("pay rent 📅 2026-10-20").as_deref(), Some("2026-10-20"));
+        assert_eq!(parse_due("pay rent 📅2026-10-20 #home").as_deref(), Some("2026-10-20"));
+        assert_eq!(parse_due("pay rent due:2026-10-20").as_deref(), Some("2026-10-20"));
+        assert_eq!(parse_due("pay rent Due: 2026-10-20").as_deref(), Some("2026-10-20"));
+        assert_eq!(parse_due("pay rent due:2026-13-40"), None);
+        assert_eq!(parse_due("pay rent by 2026-10-20"), None);
+    }
+
+    #[test]
+    fn priority_and_tags_are_parsed_from_the_text() {
+        assert_eq!(parse_priority("ship it ⏫"), Some(TaskPriority::High));
+        assert_eq!(parse_priority("ship it priority:Lowest"), Some(TaskPriority::Lowest));
+        assert_eq!(parse_priority("ship it"), None);
+        assert_eq!(parse_tags("#work/memo call #日本語, #2026 #work/memo #a-"), ["work/memo", "日本語", "a"]);
+    }
+
+    #[test]
+    fn tasks_in_front_matter_and_code_blocks_are_skipped() {
+        let content = "---\ntitle: Plan\nitems:\n- [ ] not a task\n---\n- [ ] write 📅 2026-10-20 🔼 #memo\n```\n- [ ] code\n```\n~~~md\n- [x] code too\n~~~\n  - [x] nested\n";
+        let tasks = parse_tasks(content);
+        assert_eq!(tasks.iter().map(|task| task.line).collect::<Vec<_>>(), [6, 13]);
+        let first = &tasks[0];
+        assert_eq!(first.text, "write 📅 2026-10-20 🔼 #memo");
+        assert!(!first.done);
+        assert_eq!(first.due.as_deref(), Some("2026-10-20"));
+        assert_eq!(first.priority, Some(TaskPriority::Medium));
+        assert_eq!(first.tags, ["memo"]);
+        assert!(tasks[1].done);
+    }
+
+    #[test]
+    fn toggling_keeps_crlf_line_endings() {
+        let decoded = encoding::decode(b"# Todo\r\n- [ ] one\r\n- [x] two\r\n").unwrap();
+        let (content, item) = toggle_line(&decoded.content, 2).unwrap();
+        assert!(item.done);
+        let (content, item) = toggle_line(&content, 3).unwrap();
+        assert!(!item.done);
+        assert_eq!(encoding::encode(&content, &decoded.format).unwrap(), b"# Todo\r\n- [x] one\r\n- [ ] two\r\n");
+        assert!(matches!(toggle_line(&content, 1), Err(CommandError::NotATask(1))));
+    }
+}
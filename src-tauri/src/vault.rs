@@ -0,0 +1,282 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::State;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+
+use crate::commands::{normalize_extension, CommandError, ALLOWED_MEMO_EXTENSIONS, DEFAULT_MEMO_EXTENSION};
+use crate::folder_config;
+use crate::index::IndexState;
+use crate::storage::{self, now_millis, read_json, write_json};
+
+const REGISTRY_FILE: &str = "vaults.json";
+const VAULTS_DIR: &str = "vaults";
+const SETTINGS_FILE: &str = "settings.json";
+
+/// A registered working folder
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Vault {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub added_at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VaultList {
+    pub active_id: Option<String>,
+    pub vaults: Vec<Vault>,
+}
+
+/// Settings stored per vault in `vaults/<id>/settings.json`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct VaultSettings {
+    /// Extensions listed by `list_memos` when the caller doesn't pass its own set
+    pub extensions: Vec<String>,
+    /// Extension used by `create_memo` when none is given
+    pub default_extension: String,
+    /// Commit changes made through the app when the folder is inside a git repository
+    pub git_auto_commit: bool,
+}
+
+impl Default for VaultSettings {
+    fn default() -> Self {
+        VaultSettings {
+            extensions: ALLOWED_MEMO_EXTENSIONS.iter().map(|ext| ext.to_string()).collect(),
+            default_extension: DEFAULT_MEMO_EXTENSION.to_string(),
+            git_auto_commit: false,
+        }
+    }
+}
+
+/// The vault registry persisted as `vaults.json` in the app data directory.
+/// Each vault also gets a private data directory for its settings, index and history.
+pub struct VaultRegistry {
+    data_dir: PathBuf,
+    list: VaultList,
+}
+
+impl VaultRegistry {
+    /// Reads `vaults.json`. A corrupt file is set aside (see `storage::load_or_default`) and
+    /// the registry starts empty; the error is returned for reporting.
+    pub fn load(data_dir: PathBuf) -> (Self, Option<CommandError>) {
+        let (list, error) = storage::load_or_default(&data_dir.join(REGISTRY_FILE), read_json);
+        (VaultRegistry { data_dir, list }, error)
+    }
+
+    fn save(&self) -> Result<(), CommandError> {
+        write_json(&self.data_dir.join(REGISTRY_FILE), &self.list)
+    }
+
+    pub fn get(&self, vault_id: &str) -> Result<&Vault, CommandError> {
+        self.list
+            .vaults
+            .iter()
+            .find(|vault| vault.id == vault_id)
+            .ok_or_else(|| CommandError::VaultNotFound(vault_id.to_string()))
+    }
+
+    /// Resolves the working folder of a vault, checking it still exists
+    pub fn folder(&self, vault_id: &str) -> Result<PathBuf, CommandError> {
+        let vault = self.get(vault_id)?;
+        let folder = PathBuf::from(&vault.path);
+        if !folder.is_dir() {
+            return Err(CommandError::InvalidFolder(vault.path.clone()));
+        }
+        Ok(folder)
+    }
+
+    /// Directory holding the backend's per-vault data (never inside the vault itself)
+    pub fn vault_dir(&self, vault_id: &str) -> Result<PathBuf, CommandError> {
+        self.get(vault_id)?;
+        Ok(self.data_dir.join(VAULTS_DIR).join(vault_id))
+    }
+
+    pub fn settings(&self, vault_id: &str) -> Result<VaultSettings, CommandError> {
+        read_json(&self.vault_dir(vault_id)?.join(SETTINGS_FILE))
+    }
+
+    fn active_id(&self) -> Result<&str, CommandError> {
+        self.list.active_id.as_deref().ok_or(CommandError::NoActiveFolder)
+    }
+
+    pub fn active_vault(&self) -> Result<&Vault, CommandError> {
+        self.get(self.active_id()?)
+    }
+
+    pub fn active_vault_dir(&self) -> Result<PathBuf, CommandError> {
+        self.vault_dir(self.active_id()?)
+    }
+
+    /// The registry as seen by a command addressing `vault_id`: that vault stands in for the
+    /// active one, so its folder, settings, IDs and pins are used. `None` keeps the active vault.
+    pub fn scoped(&self, vault_id: Option<&str>) -> Result<VaultRegistry, CommandError> {
+        let mut list = self.list.clone();
+        if let Some(vault_id) = vault_id {
+            list.active_id = Some(self.get(vault_id)?.id.clone());
+        }
+        Ok(VaultRegistry {
+            data_dir: self.data_dir.clone(),
+            list,
+        })
+    }
+
+    /// The working folder every memo command is validated against
+    pub fn active_folder(&self) -> Result<PathBuf, CommandError> {
+        self.folder(self.active_id()?)
+    }
+
+    /// The active vault's settings with the working folder's `.memo/config.toml` applied
+    pub fn active_settings(&self) -> Result<VaultSettings, CommandError> {
+        let mut settings = self.settings(self.active_id()?)?;
+        folder_config::load_or_default(&self.active_folder()?).apply(&mut settings);
+        Ok(settings)
+    }
+
+    /// Registers a folder as a vault, returning the existing vault if it is already known
+    pub fn register(&mut self, folder: &Path, name: Option<String>) -> Result<Vault, CommandError> {
+        let folder = folder.canonicalize()?;
+        if !folder.is_dir() {
+            return Err(CommandError::InvalidFolder(folder.to_string_lossy().to_string()));
+        }
+        let path = folder.to_string_lossy().to_string();
+        if let Some(existing) = self.list.vaults.iter().find(|vault| vault.path == path) {
+            return Ok(existing.clone());
+        }
+
+        let name = name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .or_else(|| folder.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| path.clone());
+        let vault = Vault {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            path,
+            added_at: now_millis(),
+        };
+
+        self.list.vaults.push(vault.clone());
+        self.save()?;
+        Ok(vault)
+    }
+
+    /// Makes a vault the active working folder and persists the choice
+    pub fn activate(&mut self, vault_id: &str) -> Result<Vault, CommandError> {
+        self.folder(vault_id)?;
+        let vault = self.get(vault_id)?.clone();
+        self.list.active_id = Some(vault.id.clone());
+        self.save()?;
+        Ok(vault)
+    }
+}
+
+/// Managed state wrapping the registry
+pub struct VaultState(Mutex<VaultRegistry>);
+
+impl VaultState {
+    pub fn new(registry: VaultRegistry) -> Self {
+        VaultState(Mutex::new(registry))
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, VaultRegistry> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Shows the native folder picker. Paths chosen here come from the user, not the webview.
+pub async fn pick_folder(app: &tauri::AppHandle) -> Result<Option<PathBuf>, CommandError> {
+    let (tx, rx) = oneshot::channel();
+    app.dialog().file().pick_folder(move |folder_path| {
+        let _ = tx.send(folder_path);
+    });
+
+    match rx.await {
+        Ok(Some(path)) => path
+            .into_path()
+            .map(Some)
+            .map_err(|e| CommandError::PathError(e.to_string())),
+        Ok(None) => Ok(None),
+        Err(_) => Err(CommandError::DialogCancelled),
+    }
+}
+
+/// Opens a folder dialog and registers the chosen folder as a vault.
+/// Re-adding an already registered folder returns the existing vault.
+#[tauri::command]
+pub async fn add_vault(
+    app: tauri::AppHandle,
+    state: State<'_, VaultState>,
+    name: Option<String>,
+) -> Result<Option<Vault>, CommandError> {
+    let Some(folder) = pick_folder(&app).await? else {
+        return Ok(None);
+    };
+    state.lock().register(&folder, name).map(Some)
+}
+
+/// Unregisters a vault and drops its backend data. The folder itself is left untouched.
+#[tauri::command]
+pub fn remove_vault(
+    state: State<'_, VaultState>,
+    index: State<'_, IndexState>,
+    vault_id: String,
+) -> Result<(), CommandError> {
+    let mut registry = state.lock();
+    let vault_dir = registry.vault_dir(&vault_id)?;
+
+    registry.list.vaults.retain(|vault| vault.id != vault_id);
+    if registry.list.active_id.as_deref() == Some(vault_id.as_str()) {
+        registry.list.active_id = None;
+    }
+    registry.save()?;
+    index.forget(&vault_id);
+
+    if vault_dir.exists() {
+        fs::remove_dir_all(&vault_dir)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_vaults(state: State<'_, VaultState>) -> VaultList {
+    state.lock().list.clone()
+}
+
+#[tauri::command]
+pub fn switch_vault(state: State<'_, VaultState>, vault_id: String) -> Result<Vault, CommandError> {
+    state.lock().activate(&vault_id)
+}
+
+#[tauri::command]
+pub fn get_vault_settings(state: State<'_, VaultState>, vault_id: String) -> Result<VaultSettings, CommandError> {
+    state.lock().settings(&vault_id)
+}
+
+#[tauri::command]
+pub fn update_vault_settings(
+    state: State<'_, VaultState>,
+    vault_id: String,
+    settings: VaultSettings,
+) -> Result<VaultSettings, CommandError> {
+    let extensions = settings
+        .extensions
+        .iter()
+        .map(|ext| normalize_extension(ext))
+        .collect::<Result<Vec<_>, _>>()?;
+    if extensions.is_empty() {
+        return Err(CommandError::UnsupportedExtension(String::new()));
+    }
+    let settings = VaultSettings {
+        extensions,
+        default_extension: normalize_extension(&settings.default_extension)?,
+        git_auto_commit: settings.git_auto_commit,
+    };
+
+    let registry = state.lock();
+    write_json(&registry.vault_dir(&vault_id)?.join(SETTINGS_FILE), &settings)?;
+    Ok(settings)
+}
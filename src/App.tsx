@@ -1,12 +1,19 @@
-import { useCallback } from "react";
+import { useCallback, useEffect } from "react";
 import { Layout } from "./components/Layout";
 import { Sidebar } from "./components/Sidebar";
 import { MarkdownEditor, EditorPlaceholder } from "./components/MarkdownEditor";
 import { ToastContainer } from "./components/Toast";
 import { SettingsDialog } from "./components/SettingsDialog";
 import { useMemos } from "./hooks/useMemos";
-import { useManualSave } from "./hooks/useAutoSave";
+import { useAutoSave, useManualSave } from "./hooks/useAutoSave";
 import { useFontLoader } from "./hooks/useFontLoader";
+import { useReminders } from "./hooks/useReminders";
+import { useGitAutoCommit } from "./hooks/useGitAutoCommit";
+import { useFolderConfigErrors } from "./hooks/useFolderConfigErrors";
+import { useExport } from "./hooks/useExport";
+import { useSettingsStore } from "./stores/settingsStore";
+import { showError } from "./stores/toastStore";
+import { tauriCommands } from "./tauri/commands";
 import "./App.css";
 
 function App() {
@@ -35,18 +42,59 @@ function App() {
 
   const { saveNow, flushSave } = useManualSave(currentMemo?.content || "", currentMemo?.path || null, handleSave);
 
+  // Debounced saves are queued in the backend, which also flushes them when the window closes
+  const autosaveEnabled = useSettingsStore((state) => state.settings.autosave.enabled);
+  const { flushQueued } = useAutoSave(
+    currentMemo?.content || "",
+    currentMemo?.path || null,
+    autosaveEnabled
+  );
+
+  // Settings are owned by the backend
+  const loadSettings = useSettingsStore((state) => state.loadSettings);
+  useEffect(() => {
+    loadSettings().catch((error) =>
+      console.error("Failed to load settings:", error)
+    );
+  }, [loadSettings]);
+
+  // Report data files the backend had to replace with defaults at startup
+  useEffect(() => {
+    tauriCommands
+      .takeStartupErrors()
+      .then((errors) => errors.forEach((error) => showError(`Settings were reset: ${error}`)))
+      .catch((error) => console.error("Failed to get startup errors:", error));
+  }, []);
+
   // Load custom fonts
   useFontLoader();
 
+  // Surface due reminders from the backend scheduler
+  useReminders();
+
+  // Report failed background git commits
+  useGitAutoCommit();
+
+  // Report a broken .memo/config.toml
+  useFolderConfigErrors();
+
+  // 未保存データをフラッシュ保存（自動保存が有効ならキューを書き出す）
+  const flushPending = useCallback(
+    () => (autosaveEnabled ? flushQueued() : flushSave()),
+    [autosaveEnabled, flushQueued, flushSave]
+  );
+
   const handleSelectMemo = useCallback(
     async (path: string) => {
-      // メモ切り替え前に未保存データをフラッシュ保存
-      await flushSave();
+      // メモ切り替え前に保存する
+      await flushPending();
       await openMemo(path);
     },
-    [flushSave, openMemo]
+    [flushPending, openMemo]
   );
 
+  const handleExport = useExport(currentMemo?.path || null, flushPending);
+
   return (
     <>
       <Layout
@@ -71,6 +119,7 @@ function App() {
               content={currentMemo.content}
               onChange={updateContent}
               onSave={saveNow}
+              onExport={handleExport}
             />
           ) : (
             <EditorPlaceholder />
@@ -9,12 +9,14 @@ import { $prose } from "@milkdown/utils";
 import { keymap } from "prosemirror-keymap";
 import { liftListItem } from "prosemirror-schema-list";
 import "@milkdown/theme-nord/style.css";
+import type { ExportFormat } from "../hooks/useExport";
 
 interface MarkdownEditorProps {
   memoKey: string;
   content: string;
   onChange: (content: string) => void;
   onSave?: () => void;
+  onExport?: (format: ExportFormat) => void;
 }
 
 interface MilkdownEditorProps {
@@ -151,6 +153,7 @@ export function MarkdownEditor({
   content,
   onChange,
   onSave,
+  onExport,
 }: MarkdownEditorProps) {
   const [isRawMode, setIsRawMode] = useState(false);
   const textareaRef = useRef<HTMLTextAreaElement>(null);
@@ -186,6 +189,16 @@ export function MarkdownEditor({
   return (
     <div className="editor-container" ref={containerRef} onClick={handleContainerClick}>
       <div className="editor-toolbar">
+        {onExport && (
+          <>
+            <button className="editor-toggle-btn" onClick={() => onExport("html")} title="Export as HTML">
+              <span>HTML</span>
+            </button>
+            <button className="editor-toggle-btn" onClick={() => onExport("pdf")} title="Export as PDF">
+              <span>PDF</span>
+            </button>
+          </>
+        )}
         <button
           className={`editor-toggle-btn ${isRawMode ? "active" : ""}`}
           onClick={() => setIsRawMode(!isRawMode)}
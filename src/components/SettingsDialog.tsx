@@ -1,7 +1,8 @@
-import { useCallback, useState } from "react";
+import { useCallback, useEffect, useMemo, useState } from "react";
 import { useSettingsStore } from "../stores/settingsStore";
 import { tauriCommands } from "../tauri/commands";
 import { showError, showSuccess } from "../stores/toastStore";
+import type { SystemFont } from "../types/font";
 
 export function SettingsDialog() {
   const isOpen = useSettingsStore((state) => state.settingsDialogOpen);
@@ -9,11 +10,39 @@ export function SettingsDialog() {
   const editorFont = useSettingsStore((state) => state.editorFont);
   const setEditorFont = useSettingsStore((state) => state.setEditorFont);
   const addInstalledFont = useSettingsStore((state) => state.addInstalledFont);
+  const setInstalledFonts = useSettingsStore((state) => state.setInstalledFonts);
   const resetEditorFontToDefault = useSettingsStore(
     (state) => state.resetEditorFontToDefault
   );
+  const autosave = useSettingsStore((state) => state.settings.autosave);
+  const updateSettings = useSettingsStore((state) => state.updateSettings);
 
   const [isLoading, setIsLoading] = useState(false);
+  const [systemFonts, setSystemFonts] = useState<SystemFont[]>([]);
+
+  // One entry per family; the webview picks the face
+  const systemFamilies = useMemo(() => {
+    const families = new Map<string, string[]>();
+    for (const font of systemFonts) {
+      const scripts = families.get(font.family) ?? [];
+      families.set(font.family, [...new Set([...scripts, ...font.scripts])]);
+    }
+    return [...families.entries()];
+  }, [systemFonts]);
+
+  useEffect(() => {
+    if (!isOpen) {
+      return;
+    }
+    tauriCommands
+      .listInstalledFonts()
+      .then(setInstalledFonts)
+      .catch((error) => console.error("Failed to list installed fonts:", error));
+    tauriCommands
+      .listSystemFonts()
+      .then(setSystemFonts)
+      .catch((error) => console.error("Failed to list system fonts:", error));
+  }, [isOpen, setInstalledFonts]);
 
   const handleClose = useCallback(() => {
     setOpen(false);
@@ -29,23 +58,19 @@ export function SettingsDialog() {
         return; // User cancelled
       }
 
-      // Extract label from file path
-      const fileName = fontPath.split(/[/\\]/).pop() || "Custom Font";
-      const label = fileName.replace(/\.[^.]+$/, "");
-
-      // Install font
-      const installedFont = await tauriCommands.installFont(fontPath, label);
+      // Install font (labelled with the family name read from the file)
+      const installedFont = await tauriCommands.installFont(fontPath);
       addInstalledFont(installedFont);
 
       // Set as editor font
-      setEditorFont({
+      await setEditorFont({
         type: "file",
         id: installedFont.id,
         label: installedFont.label,
         format: installedFont.format,
       });
 
-      showSuccess(`フォント「${label}」を適用しました`);
+      showSuccess(`フォント「${installedFont.label}」を適用しました`);
     } catch (error) {
       console.error("Failed to install font:", error);
       showError("フォントのインストールに失敗しました");
@@ -54,9 +79,42 @@ export function SettingsDialog() {
     }
   }, [addInstalledFont, setEditorFont]);
 
-  const handleResetToDefault = useCallback(() => {
-    resetEditorFontToDefault();
-    showSuccess("デフォルトフォントに戻しました");
+  const handleToggleAutosave = useCallback(
+    async (enabled: boolean) => {
+      try {
+        await updateSettings({ autosave: { ...autosave, enabled } });
+      } catch (error) {
+        console.error("Failed to update autosave:", error);
+        showError("自動保存の設定に失敗しました");
+      }
+    },
+    [autosave, updateSettings]
+  );
+
+  const handleSelectSystemFont = useCallback(
+    async (family: string) => {
+      if (!family) {
+        return;
+      }
+      try {
+        await setEditorFont({ type: "system", family, label: family });
+        showSuccess(`フォント「${family}」を適用しました`);
+      } catch (error) {
+        console.error("Failed to set system font:", error);
+        showError("フォントの設定に失敗しました");
+      }
+    },
+    [setEditorFont]
+  );
+
+  const handleResetToDefault = useCallback(async () => {
+    try {
+      await resetEditorFontToDefault();
+      showSuccess("デフォルトフォントに戻しました");
+    } catch (error) {
+      console.error("Failed to reset font:", error);
+      showError("フォントの設定に失敗しました");
+    }
   }, [resetEditorFontToDefault]);
 
   if (!isOpen) {
@@ -118,6 +176,41 @@ export function SettingsDialog() {
             <p className="settings-font-hint">
               対応形式: .ttf, .otf, .woff, .woff2
             </p>
+            {systemFamilies.length > 0 && (
+              <div className="settings-font-system">
+                <label
+                  className="settings-font-label"
+                  htmlFor="settings-system-font"
+                >
+                  システムのフォント:
+                </label>
+                <select
+                  id="settings-system-font"
+                  className="settings-select"
+                  value={editorFont.type === "system" ? editorFont.family : ""}
+                  onChange={(e) => handleSelectSystemFont(e.target.value)}
+                  disabled={isLoading}
+                >
+                  <option value="">選択してください</option>
+                  {systemFamilies.map(([family, scripts]) => (
+                    <option key={family} value={family}>
+                      {scripts.includes("japanese") ? `${family}（日本語）` : family}
+                    </option>
+                  ))}
+                </select>
+              </div>
+            )}
+          </div>
+          <div className="settings-section">
+            <h3 className="settings-section-title">自動保存</h3>
+            <label className="settings-checkbox">
+              <input
+                type="checkbox"
+                checked={autosave.enabled}
+                onChange={(e) => handleToggleAutosave(e.target.checked)}
+              />
+              編集が止まってから{autosave.delay_ms / 1000}秒後に保存する
+            </label>
           </div>
         </div>
       </div>
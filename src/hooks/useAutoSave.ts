@@ -1,4 +1,10 @@
 import { useRef, useCallback, useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { tauriCommands } from "../tauri/commands";
+import { useMemoStore } from "../stores/memoStore";
+import { showError } from "../stores/toastStore";
+import type { MemoMeta } from "../types/memo";
+import { SAVE_FAILED_EVENT, SAVE_FINISHED_EVENT, type SaveFailed } from "../types/autosave";
 
 export function useManualSave(
   content: string,
@@ -45,3 +51,59 @@ export function useManualSave(
 
   return { saveNow, flushSave };
 }
+
+/**
+ * 編集内容をバックエンドの自動保存キューに送る。
+ * デバウンス・書き込み・ウィンドウを閉じるときのflushはバックエンド（autosave.rs）が行う。
+ */
+export function useAutoSave(content: string, filePath: string | null, enabled: boolean) {
+  const contentRef = useRef<string>(content);
+  const filePathRef = useRef<string | null>(filePath);
+  const lastQueuedRef = useRef<string>(content);
+  const prevFilePathRef = useRef<string | null>(filePath);
+  const queuedRef = useRef<Promise<void>>(Promise.resolve());
+
+  contentRef.current = content;
+  filePathRef.current = filePath;
+
+  useEffect(() => {
+    // メモを開いた直後の内容はキューに入れない
+    if (prevFilePathRef.current !== filePath) {
+      prevFilePathRef.current = filePath;
+      lastQueuedRef.current = content;
+      return;
+    }
+    if (!enabled || !filePath || content === lastQueuedRef.current) return;
+    lastQueuedRef.current = content;
+    queuedRef.current = tauriCommands.queueSave(filePath, content).catch((error) => {
+      showError(`Autosave failed: ${error}`);
+    });
+  }, [enabled, filePath, content]);
+
+  // キューに送った内容をすべて書き込む（メモ切り替え前など）
+  const flushQueued = useCallback(async () => {
+    await queuedRef.current;
+    await tauriCommands.flushSaves();
+  }, []);
+
+  useEffect(() => {
+    const unlistenFinished = listen<MemoMeta>(SAVE_FINISHED_EVENT, (event) => {
+      const meta = event.payload;
+      const store = useMemoStore.getState();
+      store.updateMemoMeta(meta.path, meta);
+      // 保存後にさらに編集されていれば未保存のまま
+      if (meta.path === filePathRef.current && contentRef.current === lastQueuedRef.current) {
+        store.markAsSaved();
+      }
+    });
+    const unlistenFailed = listen<SaveFailed>(SAVE_FAILED_EVENT, (event) => {
+      showError(`Autosave failed: ${event.payload.error}`);
+    });
+    return () => {
+      unlistenFinished.then((fn) => fn());
+      unlistenFailed.then((fn) => fn());
+    };
+  }, []);
+
+  return { flushQueued };
+}
@@ -0,0 +1,35 @@
+import { useCallback } from "react";
+import { tauriCommands } from "../tauri/commands";
+import { showError, showSuccess } from "../stores/toastStore";
+import { useSettingsStore } from "../stores/settingsStore";
+
+export type ExportFormat = "html" | "pdf";
+
+/**
+ * Exports the current memo in the editor font. `flush` writes unsaved edits first,
+ * since the backend exports the file on disk.
+ */
+export function useExport(filePath: string | null, flush: () => Promise<void>) {
+  const editorFont = useSettingsStore((state) => state.editorFont);
+
+  return useCallback(
+    async (format: ExportFormat) => {
+      if (!filePath) return;
+      const fontId = editorFont.type === "file" ? editorFont.id : undefined;
+      const fontFamily = editorFont.type === "system" ? editorFont.family : undefined;
+      try {
+        await flush();
+        const target =
+          format === "pdf"
+            ? await tauriCommands.exportPdf(filePath, fontId, fontFamily)
+            : await tauriCommands.exportHtml(filePath, fontId);
+        if (target) {
+          showSuccess(`Exported to ${target}`);
+        }
+      } catch (error) {
+        showError(`Failed to export: ${error}`);
+      }
+    },
+    [filePath, flush, editorFont]
+  );
+}
@@ -0,0 +1,16 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { showError } from "../stores/toastStore";
+import { FOLDER_CONFIG_ERROR_EVENT } from "../types/folderConfig";
+
+export function useFolderConfigErrors() {
+  useEffect(() => {
+    // A broken shared config is ignored by the backend, so say why its settings don't apply
+    const unlisten = listen<string>(FOLDER_CONFIG_ERROR_EVENT, (event) => {
+      showError(`Folder config ignored: ${event.payload}`);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+}
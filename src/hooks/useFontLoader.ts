@@ -5,6 +5,8 @@ import { tauriCommands } from "../tauri/commands";
 
 const FONT_FAMILY_ALIAS = "MemoUserSans";
 const STYLE_ELEMENT_ID = "memo-user-font-style";
+// Custom URI scheme the backend serves installed fonts from
+const FONT_PROTOCOL = "memofont";
 
 export function useFontLoader() {
   const editorFont = useSettingsStore((state) => state.editorFont);
@@ -28,20 +30,29 @@ export function useFontLoader() {
         return;
       }
 
+      if (editorFont.type === "system") {
+        // The webview resolves system fonts by family name, so nothing is loaded
+        document.documentElement.style.setProperty(
+          "--font-user-sans",
+          `"${editorFont.family.replace(/["\\]/g, "")}"`
+        );
+        loadedFontRef.current = null;
+        return;
+      }
+
       // Skip if already loaded
       if (loadedFontRef.current === editorFont.id) {
         return;
       }
 
       try {
-        // Get the font file path from backend
-        const fontPath = await tauriCommands.getInstalledFontPath(
-          editorFont.id,
-          editorFont.format
+        // Check the font is still installed
+        const installedFont = await tauriCommands.getInstalledFont(
+          editorFont.id
         );
 
-        // Convert to asset URL
-        const fontUrl = convertFileSrc(fontPath);
+        // Served by the backend; platform-specific URL form
+        const fontUrl = convertFileSrc(installedFont.id, FONT_PROTOCOL);
 
         // Determine format for @font-face
         const formatMap: Record<string, string> = {
@@ -50,7 +61,7 @@ export function useFontLoader() {
           woff: "woff",
           woff2: "woff2",
         };
-        const fontFormat = formatMap[editorFont.format] || "truetype";
+        const fontFormat = formatMap[installedFont.format] || "truetype";
 
         // Create @font-face style
         const styleElement = document.createElement("style");
@@ -79,7 +90,9 @@ export function useFontLoader() {
         document.documentElement.style.removeProperty("--font-user-sans");
         loadedFontRef.current = null;
         // Reset setting to default
-        resetEditorFontToDefault();
+        resetEditorFontToDefault().catch((resetError) =>
+          console.error("Failed to reset font setting:", resetError)
+        );
       }
     };
 
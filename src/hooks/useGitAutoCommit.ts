@@ -0,0 +1,16 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { showError } from "../stores/toastStore";
+import { GIT_COMMIT_FAILED_EVENT } from "../types/git";
+
+export function useGitAutoCommit() {
+  useEffect(() => {
+    // Auto-commits run in the background, so failures only reach the user through this event
+    const unlisten = listen<string>(GIT_COMMIT_FAILED_EVENT, (event) => {
+      showError(`Auto-commit failed: ${event.payload}`);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+}
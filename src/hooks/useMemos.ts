@@ -2,6 +2,7 @@ import { useCallback, useEffect, useRef } from "react";
 import { useMemoStore } from "../stores/memoStore";
 import { tauriCommands } from "../tauri/commands";
 import { showError, showSuccess } from "../stores/toastStore";
+import type { Vault } from "../types/vault";
 
 function generateDefaultFileName(): string {
   const now = new Date();
@@ -14,11 +15,11 @@ export function useMemos() {
   const store = useMemoStore();
   const openMemoRequestIdRef = useRef(0);
 
-  const loadFolder = useCallback(async (folderPath: string) => {
+  const loadVault = useCallback(async (vault: Vault) => {
     store.setLoading(true);
     try {
-      store.setWorkingFolder(folderPath);
-      const memos = await tauriCommands.listMemos(folderPath);
+      store.setVault(vault);
+      const { memos } = await tauriCommands.listMemos();
       store.setMemos(memos);
       return memos;
     } catch (error) {
@@ -32,21 +33,24 @@ export function useMemos() {
 
   const openMemo = useCallback(
     async (filePath: string) => {
-      const { workingFolder } = store;
-      if (!workingFolder) return;
+      const { vaultId } = store;
+      if (!vaultId) return;
 
       // レース対策: リクエストIDをインクリメントして最新リクエストのみを反映
       const requestId = ++openMemoRequestIdRef.current;
 
       store.setLoading(true);
       try {
-        const memo = await tauriCommands.readMemo(filePath, workingFolder);
+        const memo = await tauriCommands.readMemo(filePath);
         // 最新のリクエストでない場合は結果を破棄
         if (requestId !== openMemoRequestIdRef.current) {
           return;
         }
         store.selectMemo(filePath);
         store.setCurrentMemo(memo);
+        tauriCommands.recordOpened(filePath).catch((error) => {
+          console.error("Failed to record opened memo:", error);
+        });
       } catch (error) {
         // 最新のリクエストでない場合はエラーも表示しない
         if (requestId !== openMemoRequestIdRef.current) {
@@ -66,14 +70,14 @@ export function useMemos() {
 
   const selectFolder = useCallback(async () => {
     try {
-      const folder = await tauriCommands.selectFolder();
-      if (folder) {
-        const isSameFolder = folder === store.workingFolder;
+      const vault = await tauriCommands.selectFolder();
+      if (vault) {
+        const isSameFolder = vault.id === store.vaultId;
         if (!isSameFolder) {
           store.selectMemo(null);
           store.setCurrentMemo(null);
         }
-        const memos = await loadFolder(folder);
+        const memos = await loadVault(vault);
         if (
           isSameFolder &&
           store.selectedMemoPath &&
@@ -89,14 +93,14 @@ export function useMemos() {
       console.error("Failed to select folder:", error);
       showError(`Failed to select folder: ${error}`);
     }
-  }, [loadFolder, openMemo, store]);
+  }, [loadVault, openMemo, store]);
 
   const saveMemo = useCallback(async (filePath: string, content: string) => {
-    const { workingFolder } = store;
-    if (!workingFolder) return;
+    const { vaultId } = store;
+    if (!vaultId) return;
 
     try {
-      const meta = await tauriCommands.saveMemo(filePath, content, workingFolder);
+      const meta = await tauriCommands.saveMemo(filePath, content);
       store.updateMemoMeta(filePath, meta);
       store.markAsSaved();
       showSuccess("Saved");
@@ -107,12 +111,12 @@ export function useMemos() {
   }, [store]);
 
   const createMemo = useCallback(async (name?: string) => {
-    const { workingFolder } = store;
-    if (!workingFolder) return;
+    const { vaultId } = store;
+    if (!vaultId) return;
 
     try {
       const fileName = name?.trim() || generateDefaultFileName();
-      const meta = await tauriCommands.createMemo(workingFolder, fileName);
+      const meta = await tauriCommands.createMemo(fileName);
       store.addMemo(meta);
       await openMemo(meta.path);
       showSuccess("Memo created");
@@ -123,11 +127,11 @@ export function useMemos() {
   }, [openMemo, store]);
 
   const deleteMemo = useCallback(async (filePath: string) => {
-    const { workingFolder } = store;
-    if (!workingFolder) return;
+    const { vaultId } = store;
+    if (!vaultId) return;
 
     try {
-      await tauriCommands.deleteMemo(filePath, workingFolder);
+      await tauriCommands.deleteMemo(filePath);
       store.removeMemo(filePath);
       showSuccess("Memo deleted");
     } catch (error) {
@@ -137,11 +141,11 @@ export function useMemos() {
   }, [store]);
 
   const renameMemo = useCallback(async (filePath: string, newName: string) => {
-    const { workingFolder } = store;
-    if (!workingFolder) return;
+    const { vaultId } = store;
+    if (!vaultId) return;
 
     try {
-      const meta = await tauriCommands.renameMemo(filePath, newName, workingFolder);
+      const meta = await tauriCommands.renameMemo(filePath, newName);
       store.renameMemo(filePath, meta);
       showSuccess("Memo renamed");
     } catch (error) {
@@ -156,21 +160,20 @@ export function useMemos() {
     if (initializedRef.current) return;
     initializedRef.current = true;
 
-    const workingFolder = useMemoStore.getState().workingFolder;
-    const selectedPath = useMemoStore.getState().selectedMemoPath;
-
-    if (workingFolder) {
-      loadFolder(workingFolder).then((memos) => {
-        if (!selectedPath) return;
-        if (memos?.some((memo) => memo.path === selectedPath)) {
-          openMemo(selectedPath);
-        } else {
-          useMemoStore.getState().selectMemo(null);
-          useMemoStore.getState().setCurrentMemo(null);
+    tauriCommands
+      .restoreSession()
+      .then(async (session) => {
+        if (!session) return;
+        const memos = await loadVault(session.vault);
+        const memoPath = session.memo?.path;
+        if (memoPath && memos?.some((memo) => memo.path === memoPath)) {
+          openMemo(memoPath);
         }
+      })
+      .catch((error) => {
+        console.error("Failed to restore session:", error);
       });
-    }
-  }, [loadFolder, openMemo]);
+  }, [loadVault, openMemo]);
 
   return {
     workingFolder: store.workingFolder,
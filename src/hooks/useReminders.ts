@@ -0,0 +1,18 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { showToast } from "../stores/toastStore";
+import { REMINDERS_DUE_EVENT, type Reminder } from "../types/reminder";
+
+export function useReminders() {
+  useEffect(() => {
+    // The backend also shows a desktop notification; the toast covers the focused window
+    const unlisten = listen<Reminder[]>(REMINDERS_DUE_EVENT, (event) => {
+      for (const reminder of event.payload) {
+        showToast(`${reminder.memo.name}: ${reminder.text}`, "info");
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+}
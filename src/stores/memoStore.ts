@@ -1,8 +1,9 @@
 import { create } from "zustand";
-import { persist } from "zustand/middleware";
 import type { MemoMeta, MemoFile } from "../types/memo";
+import type { Vault } from "../types/vault";
 
 interface MemoState {
+  vaultId: string | null;
   workingFolder: string | null;
   memos: MemoMeta[];
   selectedMemoPath: string | null;
@@ -10,7 +11,7 @@ interface MemoState {
   isDirty: boolean;
   isLoading: boolean;
 
-  setWorkingFolder: (folder: string | null) => void;
+  setVault: (vault: Vault | null) => void;
   setMemos: (memos: MemoMeta[]) => void;
   selectMemo: (path: string | null) => void;
   setCurrentMemo: (memo: MemoFile | null) => void;
@@ -23,75 +24,66 @@ interface MemoState {
   renameMemo: (oldPath: string, newMeta: MemoMeta) => void;
 }
 
-export const useMemoStore = create<MemoState>()(
-  persist(
-    (set) => ({
-      workingFolder: null,
-      memos: [],
-      selectedMemoPath: null,
-      currentMemo: null,
-      isDirty: false,
-      isLoading: false,
+export const useMemoStore = create<MemoState>()((set) => ({
+  vaultId: null,
+  workingFolder: null,
+  memos: [],
+  selectedMemoPath: null,
+  currentMemo: null,
+  isDirty: false,
+  isLoading: false,
 
-      setWorkingFolder: (folder) => set({ workingFolder: folder }),
-      setMemos: (memos) => set({ memos }),
-      selectMemo: (path) => set({ selectedMemoPath: path }),
-      setCurrentMemo: (memo) => set({ currentMemo: memo, isDirty: false }),
-      updateContent: (content) =>
-        set((state) => {
-          if (!state.currentMemo) return state;
-          if (state.currentMemo.content === content) return state;
-          return {
-            currentMemo: { ...state.currentMemo, content },
-            isDirty: true,
-          };
-        }),
-      markAsSaved: () => set({ isDirty: false }),
-      setLoading: (loading) => set({ isLoading: loading }),
-      addMemo: (memo) =>
-        set((state) => ({
-          memos: [memo, ...state.memos].sort(
-            (a, b) => b.modified_at - a.modified_at
-          ),
-        })),
-      removeMemo: (path) =>
-        set((state) => ({
-          memos: state.memos.filter((m) => m.path !== path),
-          selectedMemoPath:
-            state.selectedMemoPath === path ? null : state.selectedMemoPath,
-          currentMemo:
-            state.currentMemo?.path === path ? null : state.currentMemo,
-          isDirty: state.currentMemo?.path === path ? false : state.isDirty,
-        })),
-      updateMemoMeta: (path, meta) =>
-        set((state) => ({
-          memos: state.memos
-            .map((m) => (m.path === path ? { ...m, ...meta } : m))
-            .sort((a, b) => b.modified_at - a.modified_at),
-          currentMemo:
-            state.currentMemo?.path === path
-              ? { ...state.currentMemo, ...meta }
-              : state.currentMemo,
-        })),
-      renameMemo: (oldPath, newMeta) =>
-        set((state) => ({
-          memos: state.memos
-            .map((m) => (m.path === oldPath ? newMeta : m))
-            .sort((a, b) => b.modified_at - a.modified_at),
-          selectedMemoPath:
-            state.selectedMemoPath === oldPath ? newMeta.path : state.selectedMemoPath,
-          currentMemo:
-            state.currentMemo?.path === oldPath
-              ? { ...state.currentMemo, path: newMeta.path, name: newMeta.name }
-              : state.currentMemo,
-        })),
+  setVault: (vault) =>
+    set({ vaultId: vault?.id ?? null, workingFolder: vault?.path ?? null }),
+  setMemos: (memos) => set({ memos }),
+  selectMemo: (path) => set({ selectedMemoPath: path }),
+  setCurrentMemo: (memo) => set({ currentMemo: memo, isDirty: false }),
+  updateContent: (content) =>
+    set((state) => {
+      if (!state.currentMemo) return state;
+      if (state.currentMemo.content === content) return state;
+      return {
+        currentMemo: { ...state.currentMemo, content },
+        isDirty: true,
+      };
     }),
-    {
-      name: "memo-storage",
-      partialize: (state) => ({
-        workingFolder: state.workingFolder,
-        selectedMemoPath: state.selectedMemoPath,
-      }),
-    }
-  )
-);
+  markAsSaved: () => set({ isDirty: false }),
+  setLoading: (loading) => set({ isLoading: loading }),
+  addMemo: (memo) =>
+    set((state) => ({
+      memos: [memo, ...state.memos].sort(
+        (a, b) => b.modified_at - a.modified_at
+      ),
+    })),
+  removeMemo: (path) =>
+    set((state) => ({
+      memos: state.memos.filter((m) => m.path !== path),
+      selectedMemoPath:
+        state.selectedMemoPath === path ? null : state.selectedMemoPath,
+      currentMemo:
+        state.currentMemo?.path === path ? null : state.currentMemo,
+      isDirty: state.currentMemo?.path === path ? false : state.isDirty,
+    })),
+  updateMemoMeta: (path, meta) =>
+    set((state) => ({
+      memos: state.memos
+        .map((m) => (m.path === path ? { ...m, ...meta } : m))
+        .sort((a, b) => b.modified_at - a.modified_at),
+      currentMemo:
+        state.currentMemo?.path === path
+          ? { ...state.currentMemo, ...meta }
+          : state.currentMemo,
+    })),
+  renameMemo: (oldPath, newMeta) =>
+    set((state) => ({
+      memos: state.memos
+        .map((m) => (m.path === oldPath ? newMeta : m))
+        .sort((a, b) => b.modified_at - a.modified_at),
+      selectedMemoPath:
+        state.selectedMemoPath === oldPath ? newMeta.path : state.selectedMemoPath,
+      currentMemo:
+        state.currentMemo?.path === oldPath
+          ? { ...state.currentMemo, path: newMeta.path, name: newMeta.name }
+          : state.currentMemo,
+    })),
+}));
@@ -1,52 +1,88 @@
 import { create } from "zustand";
-import { persist } from "zustand/middleware";
 import type { InstalledFont, EditorFontSetting } from "../types/font";
+import type { AppSettings } from "../types/settings";
+import { tauriCommands } from "../tauri/commands";
+
+// Settings were kept in localStorage under this key before the backend owned them
+const LEGACY_STORAGE_KEY = "memo-settings";
+
+const DEFAULT_SETTINGS: AppSettings = {
+  version: 1,
+  editor_font: { type: "default" },
+  autosave: { enabled: false, delay_ms: 1000 },
+};
 
 interface SettingsState {
+  settings: AppSettings;
   editorFont: EditorFontSetting;
   installedFonts: InstalledFont[];
   settingsDialogOpen: boolean;
 
-  setEditorFont: (font: EditorFontSetting) => void;
+  loadSettings: () => Promise<void>;
+  updateSettings: (patch: Partial<AppSettings>) => Promise<void>;
+  setEditorFont: (font: EditorFontSetting) => Promise<void>;
+  setInstalledFonts: (fonts: InstalledFont[]) => void;
   addInstalledFont: (font: InstalledFont) => void;
-  removeInstalledFont: (fontId: string) => void;
-  resetEditorFontToDefault: () => void;
+  removeInstalledFont: (fontId: string) => Promise<void>;
+  resetEditorFontToDefault: () => Promise<void>;
   setSettingsDialogOpen: (open: boolean) => void;
 }
 
-export const useSettingsStore = create<SettingsState>()(
-  persist(
-    (set) => ({
-      editorFont: { type: "default" },
-      installedFonts: [],
-      settingsDialogOpen: false,
-
-      setEditorFont: (font) => set({ editorFont: font }),
-
-      addInstalledFont: (font) =>
-        set((state) => ({
-          installedFonts: [...state.installedFonts, font],
-        })),
-
-      removeInstalledFont: (fontId) =>
-        set((state) => ({
-          installedFonts: state.installedFonts.filter((f) => f.id !== fontId),
-          editorFont:
-            state.editorFont.type === "file" && state.editorFont.id === fontId
-              ? { type: "default" }
-              : state.editorFont,
-        })),
-
-      resetEditorFontToDefault: () => set({ editorFont: { type: "default" } }),
-
-      setSettingsDialogOpen: (open) => set({ settingsDialogOpen: open }),
-    }),
-    {
-      name: "memo-settings",
-      partialize: (state) => ({
-        editorFont: state.editorFont,
-        installedFonts: state.installedFonts,
-      }),
-    }
-  )
-);
+export const useSettingsStore = create<SettingsState>()((set, get) => {
+  const apply = (settings: AppSettings) =>
+    set({ settings, editorFont: settings.editor_font });
+
+  return {
+    settings: DEFAULT_SETTINGS,
+    editorFont: DEFAULT_SETTINGS.editor_font,
+    installedFonts: [],
+    settingsDialogOpen: false,
+
+    loadSettings: async () => {
+      const legacy = localStorage.getItem(LEGACY_STORAGE_KEY);
+      if (legacy) {
+        // One-time import; the backend migrates the old format
+        try {
+          apply(await tauriCommands.updateSettings(JSON.parse(legacy)));
+          localStorage.removeItem(LEGACY_STORAGE_KEY);
+          return;
+        } catch (error) {
+          console.error("Failed to import legacy settings:", error);
+          localStorage.removeItem(LEGACY_STORAGE_KEY);
+        }
+      }
+      apply(await tauriCommands.getSettings());
+    },
+
+    updateSettings: async (patch) => {
+      apply(await tauriCommands.updateSettings({ ...get().settings, ...patch }));
+    },
+
+    setEditorFont: (font) => get().updateSettings({ editor_font: font }),
+
+    setInstalledFonts: (fonts) => set({ installedFonts: fonts }),
+
+    addInstalledFont: (font) =>
+      set((state) => ({
+        // Installing a file twice returns the existing entry
+        installedFonts: [
+          ...state.installedFonts.filter((f) => f.id !== font.id),
+          font,
+        ],
+      })),
+
+    removeInstalledFont: async (fontId) => {
+      set((state) => ({
+        installedFonts: state.installedFonts.filter((f) => f.id !== fontId),
+      }));
+      const { editorFont } = get();
+      if (editorFont.type === "file" && editorFont.id === fontId) {
+        await get().setEditorFont({ type: "default" });
+      }
+    },
+
+    resetEditorFontToDefault: () => get().setEditorFont({ type: "default" }),
+
+    setSettingsDialogOpen: (open) => set({ settingsDialogOpen: open }),
+  };
+});
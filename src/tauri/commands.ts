@@ -1,37 +1,187 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { MemoMeta, MemoFile } from "../types/memo";
-import type { InstalledFont } from "../types/font";
+import type {
+  MemoMeta,
+  MemoFile,
+  MemoChunk,
+  MemoPage,
+  ListMemosOptions,
+  SaveConversion,
+} from "../types/memo";
+import type { InstalledFont, SystemFont } from "../types/font";
+import type { AppSettings } from "../types/settings";
+import type { FolderConfig } from "../types/folderConfig";
+import type { Vault, VaultList, VaultSettings } from "../types/vault";
+import type { EditorPosition, RecentMemo, RestoredSession } from "../types/session";
+import type { QuickOpenMatch } from "../types/quickOpen";
+import type { Task, TaskFilter } from "../types/task";
+import type { Reminder } from "../types/reminder";
+import type { GitCommit, GitStatus } from "../types/git";
+import type { SyncConfig, SyncReport } from "../types/sync";
+import type { EncryptionStatus } from "../types/crypto";
 
+// Memo commands work in the active vault unless given the ID of another registered vault
 export const tauriCommands = {
-  selectFolder: (): Promise<string | null> => invoke("select_folder"),
+  selectFolder: (): Promise<Vault | null> => invoke("select_folder"),
 
-  listMemos: (folderPath: string): Promise<MemoMeta[]> =>
-    invoke("list_memos", { folderPath }),
+  listMemos: (extensions?: string[], options?: ListMemosOptions, vaultId?: string): Promise<MemoPage> =>
+    invoke("list_memos", { extensions, options, vaultId }),
 
-  readMemo: (filePath: string, workingFolder: string): Promise<MemoFile> =>
-    invoke("read_memo", { filePath, workingFolder }),
+  readMemo: (filePath: string, maxSize?: number, vaultId?: string): Promise<MemoFile> =>
+    invoke("read_memo", { filePath, maxSize, vaultId }),
 
-  saveMemo: (filePath: string, content: string, workingFolder: string): Promise<MemoMeta> =>
-    invoke("save_memo", { filePath, content, workingFolder }),
+  readMemoRange: (filePath: string, offset: number, len: number, vaultId?: string): Promise<MemoChunk> =>
+    invoke("read_memo_range", { filePath, offset, len, vaultId }),
 
-  createMemo: (folderPath: string, fileName: string): Promise<MemoMeta> =>
-    invoke("create_memo", { folderPath, fileName }),
+  saveMemo: (
+    filePath: string,
+    content: string,
+    conversion?: SaveConversion,
+    vaultId?: string
+  ): Promise<MemoMeta> =>
+    invoke("save_memo", {
+      filePath,
+      content,
+      encoding: conversion?.encoding,
+      lineEnding: conversion?.lineEnding,
+      vaultId,
+    }),
 
-  deleteMemo: (filePath: string, workingFolder: string): Promise<void> =>
-    invoke("delete_memo", { filePath, workingFolder }),
+  // Autosave commands
+  queueSave: (filePath: string, content: string): Promise<void> =>
+    invoke("queue_save", { filePath, content }),
 
-  renameMemo: (filePath: string, newName: string, workingFolder: string): Promise<MemoMeta> =>
-    invoke("rename_memo", { filePath, newName, workingFolder }),
+  flushSaves: (): Promise<void> => invoke("flush_saves"),
+
+  createMemo: (fileName: string, extension?: string, vaultId?: string): Promise<MemoMeta> =>
+    invoke("create_memo", { fileName, extension, vaultId }),
+
+  deleteMemo: (filePath: string, vaultId?: string): Promise<void> =>
+    invoke("delete_memo", { filePath, vaultId }),
+
+  renameMemo: (filePath: string, newName: string, vaultId?: string): Promise<MemoMeta> =>
+    invoke("rename_memo", { filePath, newName, vaultId }),
+
+  // Vault commands
+  addVault: (name?: string): Promise<Vault | null> => invoke("add_vault", { name }),
+
+  removeVault: (vaultId: string): Promise<void> => invoke("remove_vault", { vaultId }),
+
+  listVaults: (): Promise<VaultList> => invoke("list_vaults"),
+
+  switchVault: (vaultId: string): Promise<Vault> => invoke("switch_vault", { vaultId }),
+
+  getVaultSettings: (vaultId: string): Promise<VaultSettings> =>
+    invoke("get_vault_settings", { vaultId }),
+
+  updateVaultSettings: (vaultId: string, settings: VaultSettings): Promise<VaultSettings> =>
+    invoke("update_vault_settings", { vaultId, settings }),
+
+  // Pin commands
+  pinMemo: (filePath: string): Promise<MemoMeta> => invoke("pin_memo", { filePath }),
+
+  unpinMemo: (filePath: string): Promise<void> => invoke("unpin_memo", { filePath }),
+
+  listPinned: (): Promise<MemoMeta[]> => invoke("list_pinned"),
+
+  // Memo ID commands
+  resolveMemoId: (id: string): Promise<MemoMeta> => invoke("resolve_memo_id", { id }),
+
+  // Recent files / session commands
+  recordOpened: (filePath: string, position?: EditorPosition): Promise<void> =>
+    invoke("record_opened", { filePath, position }),
+
+  getRecentMemos: (limit?: number): Promise<RecentMemo[]> =>
+    invoke("get_recent_memos", { limit }),
+
+  restoreSession: (): Promise<RestoredSession | null> => invoke("restore_session"),
+
+  quickOpen: (query: string, limit?: number): Promise<QuickOpenMatch[]> =>
+    invoke("quick_open", { query, limit }),
+
+  // Task commands
+  listTasks: (filter?: TaskFilter): Promise<Task[]> => invoke("list_tasks", { filter }),
+
+  toggleTask: (filePath: string, line: number): Promise<Task> =>
+    invoke("toggle_task", { filePath, line }),
+
+  // Reminder commands
+  listReminders: (): Promise<Reminder[]> => invoke("list_reminders"),
+
+  snoozeReminder: (key: string, minutes: number): Promise<number> =>
+    invoke("snooze_reminder", { key, minutes }),
+
+  // Git commands
+  gitStatus: (): Promise<GitStatus> => invoke("git_status"),
+
+  gitLog: (filePath?: string, limit?: number): Promise<GitCommit[]> =>
+    invoke("git_log", { filePath, limit }),
+
+  gitDiff: (filePath?: string, commit?: string): Promise<string> =>
+    invoke("git_diff", { filePath, commit }),
+
+  gitRestore: (filePath: string, commit?: string): Promise<MemoMeta> =>
+    invoke("git_restore", { filePath, commit }),
+
+  // Sync commands
+  getSyncConfig: (): Promise<SyncConfig> => invoke("get_sync_config"),
+
+  pickSyncFolder: (): Promise<SyncConfig | null> => invoke("pick_sync_folder"),
+
+  setSyncWebdav: (url: string, username?: string, password?: string): Promise<SyncConfig> =>
+    invoke("set_sync_webdav", { url, username, password }),
+
+  clearSync: (): Promise<void> => invoke("clear_sync"),
+
+  syncNow: (): Promise<SyncReport> => invoke("sync_now"),
+
+  // Encryption commands
+  encryptionStatus: (): Promise<EncryptionStatus> => invoke("encryption_status"),
+
+  // confirm repeats the passphrase; required to create the key when the folder has none yet
+  unlockEncryption: (passphrase: string, confirm?: string): Promise<EncryptionStatus> =>
+    invoke("unlock_encryption", { passphrase, confirm }),
+
+  lockEncryption: (): Promise<EncryptionStatus> => invoke("lock_encryption"),
+
+  encryptMemo: (filePath: string): Promise<MemoMeta> => invoke("encrypt_memo", { filePath }),
+
+  decryptMemo: (filePath: string): Promise<MemoMeta> => invoke("decrypt_memo", { filePath }),
+
+  // Export commands
+  exportHtml: (filePath: string, fontId?: string): Promise<string | null> =>
+    invoke("export_html", { filePath, fontId }),
+
+  // Without an installed font, the system font fontconfig matches for fontFamily (default sans-serif)
+  exportPdf: (filePath: string, fontId?: string, fontFamily?: string): Promise<string | null> =>
+    invoke("export_pdf", { filePath, fontId, fontFamily }),
+
+  // Settings commands
+  getSettings: (): Promise<AppSettings> => invoke("get_settings"),
+
+  // Data files that were corrupt at startup and replaced by defaults (returned once)
+  takeStartupErrors: (): Promise<string[]> => invoke("take_startup_errors"),
+
+  // Also accepts settings from earlier versions, which the backend migrates
+  updateSettings: (newSettings: unknown): Promise<AppSettings> =>
+    invoke("update_settings", { newSettings }),
+
+  // Folder config commands (.memo/config.toml)
+  getFolderConfig: (): Promise<FolderConfig> => invoke("get_folder_config"),
 
   // Font management commands
   pickFontFile: (): Promise<string | null> => invoke("pick_font_file"),
 
-  installFont: (fontFilePath: string, label: string): Promise<InstalledFont> =>
+  installFont: (fontFilePath: string, label?: string): Promise<InstalledFont> =>
     invoke("install_font", { fontFilePath, label }),
 
-  getInstalledFontPath: (fontId: string, format: string): Promise<string> =>
-    invoke("get_installed_font_path", { fontId, format }),
+  listInstalledFonts: (): Promise<InstalledFont[]> =>
+    invoke("list_installed_fonts"),
+
+  listSystemFonts: (): Promise<SystemFont[]> => invoke("list_system_fonts"),
+
+  getInstalledFont: (fontId: string): Promise<InstalledFont> =>
+    invoke("get_installed_font", { fontId }),
 
-  deleteInstalledFont: (fontId: string, format: string): Promise<void> =>
-    invoke("delete_installed_font", { fontId, format }),
+  deleteInstalledFont: (fontId: string): Promise<void> =>
+    invoke("delete_installed_font", { fontId }),
 };
@@ -0,0 +1,12 @@
+/** Payload of `SAVE_FAILED_EVENT` */
+export interface SaveFailed {
+  path: string;
+  error: string;
+}
+
+/** Event emitted with the memo path when a queued save starts being written */
+export const SAVE_STARTED_EVENT = "save-started";
+/** Event emitted with the saved memo's `MemoMeta` */
+export const SAVE_FINISHED_EVENT = "save-finished";
+/** Event emitted with a `SaveFailed` when a queued save can't be written */
+export const SAVE_FAILED_EVENT = "save-failed";
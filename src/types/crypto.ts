@@ -0,0 +1,5 @@
+export interface EncryptionStatus {
+  /** Whether the working folder has a key yet; the first unlock creates it */
+  configured: boolean;
+  unlocked: boolean;
+}
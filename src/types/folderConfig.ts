@@ -0,0 +1,8 @@
+/** The working folder's `.memo/config.toml`; set values override personal settings */
+export interface FolderConfig {
+  ignore: string[];
+  default_extension: string | null;
+}
+
+/** Emitted with the error of a broken `.memo/config.toml`, once per version of the file */
+export const FOLDER_CONFIG_ERROR_EVENT = "folder-config-error";
@@ -4,8 +4,27 @@ export interface InstalledFont {
   filename: string;
   format: string;
   installed_at: number;
+  sha256: string;
+  // Read from the font file; missing for fonts installed by earlier versions
+  family?: string;
+  style?: string;
+  weight?: number;
+  italic?: boolean;
+  /** e.g. ["latin", "han", "japanese"] */
+  scripts?: string[];
+}
+
+/** A font installed on the system (fontconfig), used by family name without copying */
+export interface SystemFont {
+  family: string;
+  style: string;
+  weight: number;
+  italic: boolean;
+  scripts: string[];
+  path: string;
 }
 
 export type EditorFontSetting =
   | { type: "default" }
-  | { type: "file"; id: string; label: string; format: string };
+  | { type: "file"; id: string; label: string; format: string }
+  | { type: "system"; family: string; label: string };
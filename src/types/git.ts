@@ -0,0 +1,24 @@
+export type GitFileState = "new" | "modified" | "deleted" | "renamed" | "conflicted";
+
+export interface GitFileStatus {
+  path: string;
+  state: GitFileState;
+}
+
+export interface GitStatus {
+  is_repository: boolean;
+  branch: string | null;
+  auto_commit: boolean;
+  files: GitFileStatus[];
+}
+
+export interface GitCommit {
+  id: string;
+  summary: string;
+  author: string;
+  /** Unix ms */
+  time: number;
+}
+
+/** Event emitted with the error message when an automatic commit fails */
+export const GIT_COMMIT_FAILED_EVENT = "git-commit-failed";
@@ -1,10 +1,66 @@
+export type MemoKind = "markdown" | "mdx" | "text";
+
 export interface MemoMeta {
+  id: string;
   path: string;
   name: string;
+  kind: MemoKind;
+  size: number;
   modified_at: number;
   created_at: number;
+  pinned: boolean;
+  /** Stored as `<name>.<ext>.age`; readable only after unlockEncryption */
+  encrypted: boolean;
+  preview: string | null;
+  stats: MemoStats | null;
+}
+
+export interface MemoStats {
+  words: number;
+  characters: number;
+  headings: number;
+  tasks: number;
+  tasks_done: number;
+}
+
+export type MemoSortKey = "modified" | "created" | "name" | "size";
+
+export interface ListMemosOptions {
+  sort?: MemoSortKey;
+  descending?: boolean;
+  pinned_first?: boolean;
+  cursor?: string;
+  limit?: number;
+  include_preview?: boolean;
+  include_stats?: boolean;
+}
+
+export interface MemoPage {
+  memos: MemoMeta[];
+  next_cursor: string | null;
+  total: number;
 }
 
+export type LineEnding = "lf" | "crlf";
+
 export interface MemoFile extends MemoMeta {
   content: string;
+  encoding: string;
+  has_bom: boolean;
+  line_ending: LineEnding;
+}
+
+export interface SaveConversion {
+  encoding?: string;
+  lineEnding?: LineEnding;
+}
+
+export interface MemoChunk {
+  path: string;
+  offset: number;
+  next_offset: number;
+  size: number;
+  content: string;
+  encoding: string;
+  eof: boolean;
 }
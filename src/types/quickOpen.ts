@@ -0,0 +1,17 @@
+import type { MemoMeta } from "./memo";
+
+export type MatchField = "name" | "title" | "alias" | "path";
+
+/** Highlight range in UTF-16 code units (usable with `String.prototype.slice`) */
+export interface MatchRange {
+  start: number;
+  end: number;
+}
+
+export interface QuickOpenMatch {
+  memo: MemoMeta;
+  score: number;
+  field: MatchField;
+  text: string;
+  ranges: MatchRange[];
+}
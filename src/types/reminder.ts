@@ -0,0 +1,18 @@
+import type { MemoMeta } from "./memo";
+
+export type ReminderSource = "task" | "front_matter";
+
+export interface Reminder {
+  key: string;
+  memo: MemoMeta;
+  source: ReminderSource;
+  /** 1-based line of the task (tasks only) */
+  line: number | null;
+  text: string;
+  /** Unix ms */
+  due_at: number;
+  snoozed_until: number | null;
+}
+
+/** Event emitted by the backend scheduler with the reminders that just came due */
+export const REMINDERS_DUE_EVENT = "reminders-due";
@@ -0,0 +1,18 @@
+import type { MemoMeta } from "./memo";
+import type { Vault } from "./vault";
+
+export interface EditorPosition {
+  cursor: number | null;
+  scroll_top: number | null;
+}
+
+export interface RecentMemo {
+  memo: MemoMeta;
+  opened_at: number;
+}
+
+export interface RestoredSession {
+  vault: Vault;
+  memo: MemoMeta | null;
+  position: EditorPosition;
+}
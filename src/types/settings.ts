@@ -0,0 +1,14 @@
+import type { EditorFontSetting } from "./font";
+
+export interface AutosaveSettings {
+  enabled: boolean;
+  /** Idle time after the last edit before the memo is saved (200–60000) */
+  delay_ms: number;
+}
+
+/** App-wide settings, stored by the backend in settings.json */
+export interface AppSettings {
+  version: number;
+  editor_font: EditorFontSetting;
+  autosave: AutosaveSettings;
+}
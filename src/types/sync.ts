@@ -0,0 +1,22 @@
+export type SyncTarget =
+  | { type: "directory"; path: string }
+  /** The password is never returned by the backend */
+  | { type: "webdav"; url: string; username: string | null; password: string | null };
+
+export interface SyncConfig {
+  target: SyncTarget | null;
+  /** Unix ms of the last successful sync */
+  last_synced_at: number | null;
+}
+
+/** File names touched by a sync */
+export interface SyncReport {
+  uploaded: string[];
+  downloaded: string[];
+  deleted_local: string[];
+  deleted_remote: string[];
+  /** Conflict copies holding the remote side of memos changed on both sides */
+  conflicts: string[];
+  /** Memos edited while the sync ran; picked up by the next sync */
+  skipped: string[];
+}
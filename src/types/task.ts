@@ -0,0 +1,27 @@
+import type { MemoMeta } from "./memo";
+
+export type TaskPriority = "highest" | "high" | "medium" | "low" | "lowest";
+
+export type TaskStatus = "all" | "open" | "done";
+
+export interface Task {
+  memo: MemoMeta;
+  /** 1-based line number, as passed to `toggleTask` */
+  line: number;
+  text: string;
+  done: boolean;
+  /** `YYYY-MM-DD` */
+  due: string | null;
+  priority: TaskPriority | null;
+  tags: string[];
+}
+
+export interface TaskFilter {
+  status?: TaskStatus;
+  due_before?: string;
+  due_after?: string;
+  tags?: string[];
+  min_priority?: TaskPriority;
+  query?: string;
+  file_path?: string;
+}
@@ -0,0 +1,17 @@
+export interface Vault {
+  id: string;
+  name: string;
+  path: string;
+  added_at: number;
+}
+
+export interface VaultList {
+  active_id: string | null;
+  vaults: Vault[];
+}
+
+export interface VaultSettings {
+  extensions: string[];
+  default_extension: string;
+  git_auto_commit: boolean;
+}